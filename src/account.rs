@@ -3,6 +3,7 @@ use crate::{
     error::ApiError,
     is_bad_network,
     network::get_current_block,
+    stake::{get_stake_accounts_by_authority, get_stake_balance, get_stake_context},
     types::AccountBalanceRequest,
     types::AccountBalanceResponse,
     types::{Amount, Currency},
@@ -43,6 +44,19 @@ pub fn account_balance(
     let mut balances = vec![];
     let address = &account_balance_request.account_identifier.address;
     let pubkey = address.parse::<Pubkey>()?;
+    if let Some(x) = &account_balance_request.account_identifier.sub_account {
+        if x.address != consts::STAKE_SUB_ACCOUNT {
+            return Err(ApiError::BadRequest);
+        }
+        //stake accounts the address has authority over
+        let (epoch, history) = get_stake_context(options)?;
+        let accounts = get_stake_accounts_by_authority(&pubkey, options)?;
+        let (_, _, current_block_identifier) = get_current_block(options)?;
+        return Ok(Json(AccountBalanceResponse {
+            block_identifier: current_block_identifier,
+            balances: vec![get_stake_balance(&accounts, epoch, &history)?],
+        }));
+    }
     let balance = options.rpc.get_balance(&pubkey)?;
     let native_balance = Amount {
         currency: Currency {
@@ -126,6 +140,8 @@ mod tests {
         let options = Options {
             rpc: rpc,
             network: "devnet".to_string(),
+            nonce_accounts: vec![],
+            nonce_index: Default::default(),
         };
         let network_identifier = NetworkIdentifier {
             blockchain: consts::BLOCKCHAIN.to_string(),
@@ -139,6 +155,7 @@ mod tests {
                 account_identifier: AccountIdentifier {
                     address: "Cnqmx3sbJf35852dAWvwf7GhuxMGWm5gGgw3biebSsBM".to_string(),
                     sub_account: None,
+                    metadata: None,
                 },
                 block_identifier: None,
                 currencies: None,
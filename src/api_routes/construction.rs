@@ -5,7 +5,7 @@ use crate::{
         ConstructionMetadataResponse, ConstructionParseRequest, ConstructionParseResponse,
         ConstructionPayloadsRequest, ConstructionPayloadsResponse, ConstructionPreprocessRequest,
         ConstructionPreprocessResponse, ConstructionSubmitRequest, ConstructionSubmitResponse,
-        TransactionIdentifierResponse,
+        PayoutsRequest, PayoutsResponse, TransactionIdentifierResponse,
     },
     *,
 };
@@ -80,3 +80,10 @@ pub fn construction_submit(
 ) -> Result<Json<ConstructionSubmitResponse>, ApiError> {
     construction::construction_submit(construction_submit_request.into_inner(), options.inner())
 }
+#[post("/construction/payouts", data = "<payouts_request>")]
+pub fn construction_payouts(
+    payouts_request: Json<PayoutsRequest>,
+    options: State<Options>,
+) -> Result<Json<PayoutsResponse>, ApiError> {
+    payouts::construction_payouts(payouts_request.into_inner(), options.inner())
+}
@@ -4,13 +4,17 @@ use crate::{
     error::ApiError,
     features::get_features,
     network,
+    nonce::nonce_accounts,
+    stake::stake_accounts,
     types::CallRequest,
     types::FeaturesRequest,
     types::{
         AccountBalanceRequest, AccountBalanceResponse, BlockRequest, BlockResponse,
         BlockTransactionRequest, BlockTransactionResponse, CallResponse, FeaturesResponse,
         NetworkListResponse, NetworkOptionsResponse, NetworkRequest, NetworkStatusResponse,
+        NonceRequest, NonceResponse, StakeRequest, StakeResponse, VoteRequest, VoteResponse,
     },
+    vote::vote_account,
     Options, Options2,
 };
 use rocket::State;
@@ -75,3 +79,27 @@ pub fn features(
 ) -> Result<Json<FeaturesResponse>, ApiError> {
     get_features(features_request.into_inner(), options.inner())
 }
+
+#[post("/nonce", data = "<nonce_request>")]
+pub fn nonce(
+    nonce_request: Json<NonceRequest>,
+    options: State<Options>,
+) -> Result<Json<NonceResponse>, ApiError> {
+    nonce_accounts(nonce_request.into_inner(), options.inner())
+}
+
+#[post("/stake", data = "<stake_request>")]
+pub fn stake(
+    stake_request: Json<StakeRequest>,
+    options: State<Options>,
+) -> Result<Json<StakeResponse>, ApiError> {
+    stake_accounts(stake_request.into_inner(), options.inner())
+}
+
+#[post("/vote", data = "<vote_request>")]
+pub fn vote(
+    vote_request: Json<VoteRequest>,
+    options: State<Options>,
+) -> Result<Json<VoteResponse>, ApiError> {
+    vote_account(vote_request.into_inner(), options.inner())
+}
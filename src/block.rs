@@ -12,8 +12,9 @@ use crate::{
 
 use rocket_contrib::json::Json;
 
-use solana_sdk::signature::Signature;
-use solana_transaction_status::UiTransactionEncoding;
+use solana_client::rpc_request::RpcRequest;
+use solana_sdk::{clock::Slot, signature::Signature};
+use solana_transaction_status::{EncodedConfirmedTransaction, UiTransactionEncoding};
 
 pub fn block(
     block_request: BlockRequest,
@@ -69,12 +70,83 @@ pub fn block_transaction(
             .as_str(),
     )?;
 
-    //FIXME: invalid type: null, expected struct EncodedConfirmedTransaction when tx dont exists should return Option<EncodedConfirmedTransaction> instead of directly to prevent this shold be fixed in sdk itself
-    let tx = options
-        .rpc
-        .get_confirmed_transaction(&hash, UiTransactionEncoding::JsonParsed)?;
+    let tx = get_transaction(&hash, options)?;
+    check_transaction_slot(tx.slot, &block_transaction_request.block_identifier)?;
     let response = BlockTransactionResponse {
         transaction: Transaction::from(&tx.transaction),
     };
     Ok(Json(response))
 }
+
+//transaction exists but in another block, retrying won't find it in the requested one
+fn check_transaction_slot(slot: Slot, block_identifier: &BlockIdentifier) -> Result<(), ApiError> {
+    if slot != block_identifier.index {
+        return Err(ApiError::TransactionNotInBlock(format!(
+            "transaction is in block {}, not in block {}",
+            slot, block_identifier.index
+        )));
+    }
+    Ok(())
+}
+
+//rpc_client::get_confirmed_transaction fails to deserialize null when tx dont exists so send request directly
+pub fn get_transaction(
+    signature: &Signature,
+    options: &Options,
+) -> Result<EncodedConfirmedTransaction, ApiError> {
+    let tx = options.rpc.send::<Option<EncodedConfirmedTransaction>>(
+        RpcRequest::GetConfirmedTransaction,
+        serde_json::json!([signature.to_string(), UiTransactionEncoding::JsonParsed]),
+    )?;
+    tx.ok_or(ApiError::TransactionNotFound)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        consts, create_rpc_client,
+        types::{NetworkIdentifier, TransactionIdentifier},
+    };
+
+    #[test]
+    fn check_transaction_slot_test() {
+        let block_identifier = BlockIdentifier {
+            index: 10,
+            hash: "".to_string(),
+        };
+        assert!(check_transaction_slot(10, &block_identifier).is_ok());
+        let mismatch = check_transaction_slot(11, &block_identifier).unwrap_err();
+        assert!(matches!(mismatch, ApiError::TransactionNotInBlock(_)));
+        assert!(!mismatch.retriable());
+    }
+
+    #[test]
+    #[ignore]
+    fn test_block_transaction_not_found() {
+        let options = Options {
+            rpc: create_rpc_client("https://devnet.solana.com".to_string()),
+            network: "devnet".to_string(),
+            nonce_accounts: vec![],
+            nonce_index: Default::default(),
+        };
+        let result = block_transaction(
+            BlockTransactionRequest {
+                network_identifier: NetworkIdentifier {
+                    blockchain: consts::BLOCKCHAIN.to_string(),
+                    network: "devnet".to_string(),
+                    sub_network_identifier: None,
+                },
+                block_identifier: BlockIdentifier {
+                    index: 10,
+                    hash: "".to_string(),
+                },
+                transaction_identifier: TransactionIdentifier {
+                    hash: Signature::default().to_string(),
+                },
+            },
+            &options,
+        );
+        assert!(matches!(result, Err(ApiError::TransactionNotFound)));
+    }
+}
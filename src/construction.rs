@@ -1,14 +1,21 @@
-use std::str::FromStr;
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+};
 
 use crate::{
+    consts,
     error::ApiError,
     is_bad_network,
-    operations::get_tx_from_str,
+    nonce::{check_nonce_authority, resolve_nonce},
     operations::matcher::InternalOperationMetadata,
-    operations::matcher::Matcher,
+    operations::matcher::{to_instruction_groups, InternalOperation, Matcher},
     operations::spltoken::SplTokenOperationMetadata,
-    types::{OperationType, WithNonce},
-    utils::to_pub,
+    operations::stake::StakeOperationMetadata,
+    operations::system::SystemOperationMetadata,
+    operations::vote::VoteOperationMetadata,
+    operations::{decode_tx, encode_tx, get_fee_payer, get_tx_from_str},
+    types::{Amount, Currency, OperationType, WithNonce},
     Options,
 };
 use crate::{
@@ -21,17 +28,19 @@ use crate::{
         ConstructionPayloadsResponse, ConstructionPreprocessRequest,
         ConstructionPreprocessResponse, ConstructionSubmitRequest, ConstructionSubmitResponse,
         CurveType, MetadataOptions, SignatureType, SigningPayload, TransactionIdentifier,
-        TransactionIdentifierResponse,
+        TransactionIdentifierResponse, UnsignedTransaction,
     },
 };
 use rocket_contrib::json::Json;
-use serde::{Deserialize, Serialize};
-use solana_account_decoder::{UiAccount, UiAccountData, UiAccountEncoding, UiFeeCalculator};
 use solana_sdk::{
-    fee_calculator::FeeCalculator, hash::Hash, message::Message, program_pack::Pack,
-    pubkey::Pubkey, signature::Signature, transaction::Transaction,
+    account::from_account, clock::Clock, hash::Hash, instruction::Instruction, message::Message,
+    nonce, packet::PACKET_DATA_SIZE, program_option::COption, program_pack::Pack, pubkey::Pubkey,
+    signature::Signature, system_program, sysvar, transaction::Transaction,
 };
-use solana_transaction_status::{EncodedTransaction, UiMessage, UiTransactionEncoding};
+use solana_stake_program::stake_state::{Lockup, StakeState};
+use solana_transaction_status::{EncodedTransaction, UiTransactionEncoding};
+use solana_vote_program::vote_state::VoteState;
+use spl_associated_token_account::get_associated_token_address;
 use spl_token::state::{Account, Mint};
 
 pub fn construction_derive(
@@ -44,14 +53,51 @@ pub fn construction_derive(
         return Err(ApiError::UnsupportedCurve);
     };
     let hex_pubkey = hex::decode(&construction_derive_request.public_key.hex_bytes)?;
-    let bs58_pubkey = bs58::encode(hex_pubkey).into_string();
-
-    let response = ConstructionDeriveResponse {
-        account_identifier: AccountIdentifier {
-            address: bs58_pubkey,
+    if hex_pubkey.len() != 32 {
+        return Err(ApiError::BadRequest);
+    }
+    let pubkey = Pubkey::new(&hex_pubkey);
+    let metadata = construction_derive_request.metadata.unwrap_or_default();
+    let program = if let Some(x) = &metadata.owner {
+        Pubkey::from_str(x)?
+    } else {
+        system_program::id()
+    };
+    let owner = if let Some(seed) = &metadata.seed {
+        Pubkey::create_with_seed(&pubkey, seed, &program).map_err(|_| ApiError::BadRequest)?
+    } else {
+        pubkey
+    };
+    //token deposit address of owner for mint
+    let account_identifier = if let Some(mint) = &metadata.mint {
+        let mint = Pubkey::from_str(mint)?;
+        AccountIdentifier {
+            address: get_associated_token_address(&owner, &mint).to_string(),
             sub_account: None,
-        },
+            metadata: Some(serde_json::json!({
+                "owner": owner.to_string(),
+                "mint": mint.to_string(),
+            })),
+        }
+    } else if let Some(seed) = &metadata.seed {
+        AccountIdentifier {
+            address: owner.to_string(),
+            sub_account: None,
+            metadata: Some(serde_json::json!({
+                "base": pubkey.to_string(),
+                "seed": seed,
+                "owner": program.to_string(),
+            })),
+        }
+    } else {
+        AccountIdentifier {
+            address: owner.to_string(),
+            sub_account: None,
+            metadata: None,
+        }
     };
+
+    let response = ConstructionDeriveResponse { account_identifier };
     Ok(Json(response))
 }
 pub fn construction_hash(
@@ -81,15 +127,17 @@ pub fn construction_preprocess(
     let mut matcher = Matcher::new(&construction_preprocess_request.operations, None);
     let internal_operations = matcher.combine()?;
 
-    let with_nonce = if let Some(x) = construction_preprocess_request.metadata {
-        x.with_nonce
+    let (with_nonce, encoding, split) = if let Some(x) = construction_preprocess_request.metadata {
+        (x.with_nonce, x.encoding, x.split)
     } else {
-        None
+        (None, None, None)
     };
     let response = ConstructionPreprocessResponse {
         options: Some(MetadataOptions {
             internal_operations,
             with_nonce,
+            encoding,
+            split,
         }),
     };
     Ok(Json(response))
@@ -101,95 +149,456 @@ pub fn construction_metadata(
     options: &Options,
 ) -> Result<Json<ConstructionMetadataResponse>, ApiError> {
     is_bad_network(&options, &construction_metadata_request.network_identifier)?;
-    #[serde(rename_all = "camelCase")]
-    #[derive(Default, Serialize, Deserialize, Clone, Debug)]
-    struct Info {
-        authority: String,
-        blockhash: String,
-        fee_calculator: UiFeeCalculator,
-    }
-    #[derive(Default, Serialize, Deserialize, Clone, Debug)]
-    struct Parsed {
-        info: Info,
-    }
     //optional metadata for some special types
     let mut with_nonce = None;
-    let mut parsed = Parsed::default();
+    let mut encoding = None;
+    let mut split = None;
+    let mut nonce_data = None;
+    let mut created_accounts = 0;
+    let mut signatures = 0;
     let internal_meta = if let Some(x) = &construction_metadata_request.options {
+        encoding = x.encoding;
+        split = x.split;
+        let mut signers = HashSet::new();
         if let Some(n) = &x.with_nonce {
-            let pubkey = &to_pub(&n.account);
-            let acc = options.rpc.get_account(pubkey)?;
-            let uiacc = UiAccount::encode(pubkey, acc, UiAccountEncoding::JsonParsed, None, None);
-            if let UiAccountData::Json(parsed_acc) = uiacc.data {
-                parsed = serde_json::from_value::<Parsed>(parsed_acc.parsed).unwrap();
-                with_nonce = Some(WithNonce {
-                    account: n.account.clone(),
-                    authority: Some(parsed.info.authority),
-                });
+            let (nonce, data) = resolve_nonce(n, options)?;
+            signers.insert(data.authority);
+            with_nonce = Some(nonce);
+            nonce_data = Some(data);
+        };
+        let mut created = CreatedAccounts::new();
+        let mut ops = vec![];
+        for operation in &x.internal_operations {
+            let meta = get_operation_metadata(operation, &created, options)?;
+            add_created_accounts(&mut created, operation, &meta);
+            ops.push(meta);
+        }
+        //signers of the instructions built by construction_payloads
+        let mut operations = x.internal_operations.clone();
+        for (operation, meta) in operations.iter_mut().zip(&ops) {
+            operation.merge_metadata(meta);
+        }
+        signatures = if split == Some(true) {
+            //every transaction of a split pays for its own signatures
+            split_transactions(operations, Hash::default())?
+                .iter()
+                .map(|tx| tx.message.header.num_required_signatures as usize)
+                .sum()
+        } else {
+            for instruction in to_instruction_groups(operations)?.concat() {
+                signers.extend(
+                    instruction
+                        .accounts
+                        .iter()
+                        .filter(|x| x.is_signer)
+                        .map(|x| x.pubkey),
+                );
             }
+            signers.len()
         };
-        let ops = x
-            .internal_operations
+        created_accounts = ops
             .iter()
-            .map(|x| match x.type_ {
-                //TODO: Add more metadata as required
-                OperationType::SplToken__CreateAccount => {
-                    let rent = options
-                        .rpc
-                        .get_minimum_balance_for_rent_exemption(Account::LEN)
-                        .unwrap();
+            .filter(|x| {
+                matches!(
+                    x,
                     Some(InternalOperationMetadata::SplToken(
                         SplTokenOperationMetadata {
-                            amount: Some(rent),
-                            ..Default::default()
-                        },
-                    ))
-                }
-                OperationType::SplToken__CreateToken => {
-                    let rent = options
-                        .rpc
-                        .get_minimum_balance_for_rent_exemption(Mint::LEN)
-                        .unwrap();
-                    Some(InternalOperationMetadata::SplToken(
-                        SplTokenOperationMetadata {
-                            amount: Some(rent),
-                            ..Default::default()
-                        },
+                            destination_exists: Some(false),
+                            ..
+                        }
                     ))
-                }
-                _ => None,
+                )
             })
-            .collect::<Vec<Option<InternalOperationMetadata>>>();
+            .count() as u64;
         Some(ops)
     } else {
         None
     };
     //required metadata
-    let (hash, fee_calculator) = if with_nonce.is_none() {
-        options.rpc.get_recent_blockhash()?
+    let (hash, fee_calculator) = if let Some(data) = nonce_data {
+        (data.blockhash, data.fee_calculator)
     } else {
-        (
-            Hash::from_str(&parsed.info.blockhash).unwrap(),
-            FeeCalculator::new(
-                parsed
-                    .info
-                    .fee_calculator
-                    .lamports_per_signature
-                    .parse::<u64>()
-                    .unwrap(),
-            ),
-        )
+        options.rpc.get_recent_blockhash()?
     };
+    //fee of every required signature and rent of associated token accounts created for transfers
+    let mut fee = fee_calculator.lamports_per_signature * signatures.max(1) as u64;
+    if created_accounts > 0 {
+        fee += created_accounts
+            * options
+                .rpc
+                .get_minimum_balance_for_rent_exemption(Account::LEN)?;
+    }
     let response = ConstructionMetadataResponse {
         metadata: ConstructionMetadata {
             blockhash: hash.to_string(),
             fee_calculator,
             internal_meta,
             with_nonce,
+            encoding,
+            split,
         },
+        suggested_fee: Some(vec![Amount {
+            value: fee.to_string(),
+            currency: Currency {
+                symbol: consts::NATIVE_SYMBOL.to_string(),
+                decimals: consts::NATIVE_DECIMALS,
+                metadata: None,
+            },
+        }]),
     };
     Ok(Json(response))
 }
+//accounts created by earlier operations of the same request with their mint and decimals
+//they are not on chain yet so rpc lookups are skipped for them
+type CreatedAccounts = HashMap<Pubkey, (Option<Pubkey>, Option<u8>)>;
+
+fn add_created_accounts(
+    created: &mut CreatedAccounts,
+    operation: &InternalOperation,
+    meta: &Option<InternalOperationMetadata>,
+) {
+    let parse = |x: &Option<String>| x.as_ref().and_then(|x| x.parse::<Pubkey>().ok());
+    match &operation.metadata {
+        InternalOperationMetadata::System(x) => {
+            if operation.type_ == OperationType::System__CreateAccount {
+                if let Some(destination) = parse(&x.destination) {
+                    created.insert(destination, (None, None));
+                }
+            }
+        }
+        InternalOperationMetadata::SplToken(x) => {
+            let mint = parse(&x.mint);
+            let account = match operation.type_ {
+                OperationType::SplToken__CreateToken => {
+                    mint.map(|m| (m, (Some(m), Some(x.decimals.unwrap_or(2)))))
+                }
+                OperationType::SplToken__InitializeMint => mint.map(|m| (m, (Some(m), x.decimals))),
+                OperationType::SplToken__CreateAccount
+                | OperationType::SplToken__InitializeAccount => {
+                    parse(&x.destination).map(|d| (d, (mint, None)))
+                }
+                OperationType::SplToken__CreateAssocAccount => match (parse(&x.source), mint) {
+                    (Some(s), Some(m)) => {
+                        Some((get_associated_token_address(&s, &m), (mint, None)))
+                    }
+                    _ => None,
+                },
+                //associated token account created before the transfer
+                OperationType::SplToken__Transfer | OperationType::SplToken__TransferChecked => {
+                    match meta {
+                        Some(InternalOperationMetadata::SplToken(SplTokenOperationMetadata {
+                            destination: Some(d),
+                            mint: Some(m),
+                            destination_exists: Some(false),
+                            ..
+                        })) => match (d.parse::<Pubkey>(), m.parse::<Pubkey>()) {
+                            (Ok(d), Ok(m)) => Some((d, (Some(m), None))),
+                            _ => None,
+                        },
+                        _ => None,
+                    }
+                }
+                _ => None,
+            };
+            if let Some((address, data)) = account {
+                created.insert(address, data);
+            }
+        }
+        _ => {}
+    }
+}
+//metadata that needs rpc access e.g rent exempt balances and mint decimals
+//values already given in operation metadata take precedence when merged in matcher
+fn get_operation_metadata(
+    operation: &InternalOperation,
+    created: &CreatedAccounts,
+    options: &Options,
+) -> Result<Option<InternalOperationMetadata>, ApiError> {
+    let rent = |size: usize| options.rpc.get_minimum_balance_for_rent_exemption(size);
+    Ok(match &operation.metadata {
+        InternalOperationMetadata::System(x) => {
+            let size = match operation.type_ {
+                OperationType::System__CreateAccount
+                | OperationType::System__CreateAccountWithSeed => x.space.unwrap_or(0) as usize,
+                OperationType::System__CreateNonceAccount => nonce::State::size(),
+                _ => return Ok(None),
+            };
+            Some(InternalOperationMetadata::System(SystemOperationMetadata {
+                lamports: Some(rent(size)?),
+                ..Default::default()
+            }))
+        }
+        InternalOperationMetadata::Stake(x) => match operation.type_ {
+            //the amount to stake is up to the caller, it only has to cover rent exemption
+            OperationType::Stake__CreateAccount | OperationType::Stake__CreateAccountWithSeed => {
+                let lamports = x
+                    .lamports
+                    .ok_or_else(|| ApiError::BadOperations("lamports missing".to_string()))?;
+                let minimum = rent(std::mem::size_of::<StakeState>())?;
+                if lamports < minimum {
+                    return Err(ApiError::BadOperations(format!(
+                        "{} lamports is below the rent exempt minimum of {}",
+                        lamports, minimum
+                    )));
+                }
+                None
+            }
+            OperationType::Stake__Withdraw => {
+                let source = x
+                    .source
+                    .as_ref()
+                    .ok_or_else(|| ApiError::BadOperations("source missing".to_string()))?;
+                let acc = options
+                    .rpc
+                    .get_account_with_commitment(
+                        &source.parse::<Pubkey>()?,
+                        options.rpc.commitment(),
+                    )?
+                    .value
+                    .ok_or(ApiError::AccountNotFound)?;
+                if let Some(lockup) = StakeState::lockup_from(&acc) {
+                    let clock =
+                        from_account::<Clock>(&options.rpc.get_account(&sysvar::clock::id())?)
+                            .ok_or_else(|| ApiError::deserialization_failed("Clock"))?;
+                    let custodian = match &x.custodian {
+                        Some(c) => Some(c.parse::<Pubkey>()?),
+                        None => None,
+                    };
+                    check_lockup(&lockup, &clock, custodian.as_ref(), source)?;
+                }
+                None
+            }
+            _ => None,
+        },
+        InternalOperationMetadata::Memo(_) => None,
+        InternalOperationMetadata::Vote(_) => match operation.type_ {
+            OperationType::Vote__CreateAccount => {
+                Some(InternalOperationMetadata::Vote(VoteOperationMetadata {
+                    lamports: Some(rent(VoteState::size_of())?),
+                    ..Default::default()
+                }))
+            }
+            _ => None,
+        },
+        InternalOperationMetadata::SplToken(x) => match operation.type_ {
+            OperationType::SplToken__CreateAccount => Some(InternalOperationMetadata::SplToken(
+                SplTokenOperationMetadata {
+                    amount: Some(rent(Account::LEN)?),
+                    ..Default::default()
+                },
+            )),
+            OperationType::SplToken__CreateToken => Some(InternalOperationMetadata::SplToken(
+                SplTokenOperationMetadata {
+                    amount: Some(rent(Mint::LEN)?),
+                    ..Default::default()
+                },
+            )),
+            OperationType::SplToken__Transfer | OperationType::SplToken__TransferChecked => {
+                //make sure accounts exist and source has enough tokens
+                let source = x
+                    .source
+                    .as_ref()
+                    .ok_or_else(|| ApiError::BadOperations("source missing".to_string()))?;
+                let source_pubkey = source.parse::<Pubkey>()?;
+                let token_account = if created.contains_key(&source_pubkey) {
+                    None
+                } else {
+                    let token_account = get_token_account(&source_pubkey, options)?;
+                    check_token_balance(&token_account, x, source)?;
+                    Some(token_account)
+                };
+                let (mint, decimals) = get_mint_and_decimals(
+                    &operation.type_,
+                    x,
+                    token_account.as_ref(),
+                    created,
+                    options,
+                )?;
+                let mut destination = None;
+                let mut destination_exists = None;
+                if x.create_destination.unwrap_or(false) {
+                    let owner = x.destination_owner.as_ref().ok_or_else(|| {
+                        ApiError::BadOperations("destination_owner missing".to_string())
+                    })?;
+                    let address = get_associated_token_address(&owner.parse::<Pubkey>()?, &mint);
+                    let exists = created.contains_key(&address)
+                        || options
+                            .rpc
+                            .get_account_with_commitment(&address, options.rpc.commitment())?
+                            .value
+                            .is_some();
+                    destination = Some(address.to_string());
+                    destination_exists = Some(exists);
+                } else if let Some(d) = &x.destination {
+                    let destination = d.parse::<Pubkey>()?;
+                    if !created.contains_key(&destination) {
+                        get_token_account(&destination, options)?;
+                    }
+                }
+                Some(InternalOperationMetadata::SplToken(
+                    SplTokenOperationMetadata {
+                        mint: Some(mint.to_string()),
+                        decimals,
+                        destination,
+                        destination_exists,
+                        ..Default::default()
+                    },
+                ))
+            }
+            OperationType::SplToken__MintTo
+            | OperationType::SplToken__Burn
+            | OperationType::SplToken__MintToChecked
+            | OperationType::SplToken__BurnChecked
+            | OperationType::SplToken__ApproveChecked => {
+                if x.mint.is_none() && x.source.is_none() {
+                    return Ok(None);
+                }
+                //delegate burns are limited to the approved amount
+                let mut token_account = None;
+                let burn = matches!(
+                    &operation.type_,
+                    OperationType::SplToken__Burn | OperationType::SplToken__BurnChecked
+                );
+                if let (true, Some(source), Some(_)) = (burn, &x.source, &x.delegate) {
+                    let source_pubkey = source.parse::<Pubkey>()?;
+                    if !created.contains_key(&source_pubkey) {
+                        let account = get_token_account(&source_pubkey, options)?;
+                        check_token_balance(&account, x, source)?;
+                        token_account = Some(account);
+                    }
+                }
+                let (mint, decimals) = get_mint_and_decimals(
+                    &operation.type_,
+                    x,
+                    token_account.as_ref(),
+                    created,
+                    options,
+                )?;
+                Some(InternalOperationMetadata::SplToken(
+                    SplTokenOperationMetadata {
+                        mint: Some(mint.to_string()),
+                        decimals,
+                        ..Default::default()
+                    },
+                ))
+            }
+            _ => None,
+        },
+    })
+}
+//withdrawals of locked stake need the custodian signature
+fn check_lockup(
+    lockup: &Lockup,
+    clock: &Clock,
+    custodian: Option<&Pubkey>,
+    stake: &str,
+) -> Result<(), ApiError> {
+    if !lockup.is_in_force(clock, custodian) {
+        return Ok(());
+    }
+    Err(ApiError::StakeLocked(format!(
+        "{} is locked until epoch {} and unix timestamp {} (now epoch {} and unix timestamp {}), withdrawals need custodian {} signature",
+        stake,
+        lockup.epoch,
+        lockup.unix_timestamp,
+        clock.epoch,
+        clock.unix_timestamp,
+        lockup.custodian
+    )))
+}
+//balance of source or the amount approved for delegate
+fn check_token_balance(
+    token_account: &Account,
+    metadata: &SplTokenOperationMetadata,
+    source: &str,
+) -> Result<(), ApiError> {
+    let amount = metadata.amount.unwrap_or(0);
+    if let Some(delegate) = &metadata.delegate {
+        if token_account.delegate != COption::Some(delegate.parse::<Pubkey>()?) {
+            return Err(ApiError::BadOperations(format!(
+                "{} is not delegate of {}",
+                delegate, source
+            )));
+        }
+        if token_account.delegated_amount < amount {
+            return Err(ApiError::InsufficientBalance(format!(
+                "{} approved for {}",
+                token_account.delegated_amount, delegate
+            )));
+        }
+    }
+    if token_account.amount < amount {
+        return Err(ApiError::InsufficientBalance(source.to_string()));
+    }
+    Ok(())
+}
+//mint missing from the operation and decimals of checked instructions
+//looked up only when not given, accounts created in the same request are not on chain yet
+fn get_mint_and_decimals(
+    type_: &OperationType,
+    metadata: &SplTokenOperationMetadata,
+    token_account: Option<&Account>,
+    created: &CreatedAccounts,
+    options: &Options,
+) -> Result<(Pubkey, Option<u8>), ApiError> {
+    let mint = match (&metadata.mint, token_account) {
+        (Some(m), _) => m.parse::<Pubkey>()?,
+        (None, Some(t)) => t.mint,
+        (None, None) => get_token_mint(metadata, created, options)?,
+    };
+    let checked = matches!(
+        type_,
+        OperationType::SplToken__TransferChecked
+            | OperationType::SplToken__MintToChecked
+            | OperationType::SplToken__BurnChecked
+            | OperationType::SplToken__ApproveChecked
+    );
+    let decimals = if checked && metadata.decimals.is_none() {
+        Some(get_mint_decimals(&mint, created, options)?)
+    } else {
+        None
+    };
+    Ok((mint, decimals))
+}
+//mint of source token account is used if not given
+fn get_token_mint(
+    metadata: &SplTokenOperationMetadata,
+    created: &CreatedAccounts,
+    options: &Options,
+) -> Result<Pubkey, ApiError> {
+    if let Some(m) = &metadata.mint {
+        return Ok(m.parse::<Pubkey>()?);
+    }
+    let source = metadata
+        .source
+        .as_ref()
+        .ok_or_else(|| ApiError::BadOperations("mint missing".to_string()))?
+        .parse::<Pubkey>()?;
+    match created.get(&source) {
+        Some((mint, _)) => mint.ok_or_else(|| ApiError::BadOperations("mint missing".to_string())),
+        None => Ok(get_token_account(&source, options)?.mint),
+    }
+}
+fn get_mint_decimals(
+    mint: &Pubkey,
+    created: &CreatedAccounts,
+    options: &Options,
+) -> Result<u8, ApiError> {
+    match created.get(mint) {
+        Some((_, decimals)) => {
+            decimals.ok_or_else(|| ApiError::BadOperations("decimals missing".to_string()))
+        }
+        None => Ok(Mint::unpack(&options.rpc.get_account(mint)?.data)?.decimals),
+    }
+}
+fn get_token_account(address: &Pubkey, options: &Options) -> Result<Account, ApiError> {
+    let acc = options
+        .rpc
+        .get_account_with_commitment(address, options.rpc.commitment())?
+        .value
+        .ok_or(ApiError::AccountNotFound)?;
+    Ok(Account::unpack(&acc.data)?)
+}
 //Construct Payloads to Sign
 
 pub fn construction_payloads(
@@ -199,8 +608,12 @@ pub fn construction_payloads(
     is_bad_network(&options, &construction_payloads_request.network_identifier)?;
 
     let mut with_nonce = None;
+    let mut encoding = None;
+    let mut split = false;
     let meta = if let Some(x) = &construction_payloads_request.metadata {
         with_nonce = x.with_nonce.clone();
+        encoding = x.encoding;
+        split = x.split.unwrap_or(false);
         if let Some(x) = &x.internal_meta {
             Some(x.clone())
         } else {
@@ -210,45 +623,157 @@ pub fn construction_payloads(
         None
     };
     let mut matcher = Matcher::new(&construction_payloads_request.operations, meta);
-    let instructions = matcher.to_instructions()?;
-    let mut fee_payer = None;
-    let mut fee_payer_pub = None;
-    instructions.iter().for_each(|x| {
-        if let Some(y) = x.accounts.iter().find(|a| a.is_signer) {
-            fee_payer_pub = Some(y.pubkey.clone());
+    let operations = matcher.combine()?;
+    let groups = to_instruction_groups(operations.clone())?;
+
+    //nonce blockhash is used if given so payloads can be built offline without metadata call
+    let blockhash = match (&with_nonce, &construction_payloads_request.metadata) {
+        (
+            Some(WithNonce {
+                blockhash: Some(x), ..
+            }),
+            _,
+        ) => x.clone(),
+        (_, Some(x)) if !x.blockhash.is_empty() => x.blockhash.clone(),
+        _ => return Err(ApiError::BadTransactionPayload),
+    };
+    let blockhash = Hash::from_str(&blockhash).map_err(|_| ApiError::BadTransactionPayload)?;
+    let nonce = if let Some(x) = with_nonce {
+        match (x.account, x.authority) {
+            (Some(account), Some(authority)) => {
+                let authority = authority.parse::<Pubkey>()?;
+                check_nonce_authority(&authority, &groups.concat())?;
+                Some((account.parse::<Pubkey>()?, authority))
+            }
+            _ => return Err(ApiError::BadTransactionPayload),
+        }
+    } else {
+        None
+    };
+
+    let transactions = if split {
+        //every transaction would need its own nonce account
+        if nonce.is_some() {
+            return Err(ApiError::BadOperations(
+                "nonce transactions can't be split".to_string(),
+            ));
         }
-    });
-    if let Some(x) = &fee_payer_pub {
-        fee_payer = Some(x);
+        split_transactions(operations, blockhash)?
+    } else {
+        vec![build_transaction(&groups.concat(), &nonce, blockhash)]
     };
 
-    let msg = if let Some(x) = with_nonce {
+    let mut unsigned_transactions = vec![];
+    for tx in &transactions {
+        check_limits(tx)?;
+        unsigned_transactions.push(UnsignedTransaction {
+            unsigned_transaction: encode_tx(tx, encoding.unwrap_or_default())?,
+            payloads: get_signing_payloads(tx),
+        });
+    }
+    let first = unsigned_transactions[0].clone();
+    let response = ConstructionPayloadsResponse {
+        unsigned_transaction: first.unsigned_transaction,
+        payloads: first.payloads,
+        transactions: if split {
+            Some(unsigned_transactions)
+        } else {
+            None
+        },
+    };
+    Ok(Json(response))
+}
+//pack instruction groups into as few transactions as possible
+//returns transactions and index of transaction of every group
+pub(crate) fn pack_transactions(
+    groups: Vec<Vec<Instruction>>,
+    blockhash: Hash,
+) -> (Vec<Transaction>, Vec<usize>) {
+    let mut transactions = vec![];
+    let mut indexes = vec![];
+    let mut current: Vec<Instruction> = vec![];
+    for group in groups {
+        let mut candidate = current.clone();
+        candidate.extend(group.iter().cloned());
+        if current.is_empty()
+            || check_limits(&build_transaction(&candidate, &None, blockhash)).is_ok()
+        {
+            current = candidate;
+        } else {
+            transactions.push(build_transaction(&current, &None, blockhash));
+            current = group;
+        }
+        indexes.push(transactions.len());
+    }
+    if !current.is_empty() || transactions.is_empty() {
+        transactions.push(build_transaction(&current, &None, blockhash));
+    }
+    (transactions, indexes)
+}
+//pack operations into transactions, accounts created for transfers are funded by the fee payer
+//of the transaction they end up in
+fn split_transactions(
+    operations: Vec<InternalOperation>,
+    blockhash: Hash,
+) -> Result<Vec<Transaction>, ApiError> {
+    let (packed, indexes) =
+        pack_transactions(to_instruction_groups(operations.clone())?, blockhash);
+    let mut transactions = vec![];
+    for i in 0..packed.len() {
+        let operations = operations
+            .iter()
+            .zip(&indexes)
+            .filter(|(_, x)| **x == i)
+            .map(|(x, _)| x.clone())
+            .collect();
+        //funder only changes to a signer already in the transaction so it still fits
+        let instructions = to_instruction_groups(operations)?.concat();
+        transactions.push(build_transaction(&instructions, &None, blockhash));
+    }
+    Ok(transactions)
+}
+fn build_transaction(
+    instructions: &[Instruction],
+    nonce: &Option<(Pubkey, Pubkey)>,
+    blockhash: Hash,
+) -> Transaction {
+    let fee_payer = get_fee_payer(instructions);
+    let msg = if let Some((account, authority)) = nonce {
         Message::new_with_nonce(
-            instructions,
-            fee_payer,
-            &to_pub(&x.account),
-            &to_pub(&x.authority.unwrap()),
+            instructions.to_vec(),
+            fee_payer.as_ref(),
+            account,
+            authority,
         )
     } else {
-        Message::new(&instructions, fee_payer)
+        Message::new(instructions, fee_payer.as_ref())
     };
     let mut tx = Transaction::new_unsigned(msg);
-    //recent_blockhash is required as metadata
-    if let Some(x) = &construction_payloads_request.metadata {
-        let h = bs58::decode(&x.blockhash).into_vec().unwrap();
-        tx.message.recent_blockhash = Hash::new(&h);
-    } else {
-        return Err(ApiError::BadTransactionPayload);
+    tx.message.recent_blockhash = blockhash;
+    tx
+}
+//packet size and account limits, checked with empty signatures
+pub(crate) fn check_limits(tx: &Transaction) -> Result<(), ApiError> {
+    let size = bincode::serialized_size(tx).map_err(|_| ApiError::BadTransactionPayload)? as usize;
+    if size > PACKET_DATA_SIZE {
+        return Err(ApiError::TransactionTooLarge(format!(
+            "transaction size {} bytes exceeds limit of {} bytes",
+            size, PACKET_DATA_SIZE
+        )));
     }
-
-    let v = bincode::serialize(&tx);
-    if v.is_err() {
-        return Err(ApiError::BadTransactionPayload);
+    let accounts = tx.message.account_keys.len();
+    if accounts > consts::MAX_TX_ACCOUNTS {
+        return Err(ApiError::TransactionTooLarge(format!(
+            "transaction uses {} accounts, limit is {}",
+            accounts,
+            consts::MAX_TX_ACCOUNTS
+        )));
     }
-    let unsigned_transaction = bs58::encode(v.unwrap()).into_string();
+    Ok(())
+}
+pub(crate) fn get_signing_payloads(tx: &Transaction) -> Vec<Option<SigningPayload>> {
     let to_be_signed = hex::encode(tx.message.serialize());
-    let signing_payloads = tx
-        .message
+    tx.message
         .account_keys
         .iter()
         .enumerate()
@@ -258,6 +783,7 @@ pub fn construction_payloads(
                     account_identifier: Some(AccountIdentifier {
                         address: bs58::encode(pubk.to_bytes()).into_string(),
                         sub_account: None,
+                        metadata: None,
                     }),
                     hex_bytes: to_be_signed.clone(),
                     signature_type: Some(SignatureType::Ed25519),
@@ -267,13 +793,7 @@ pub fn construction_payloads(
             }
         })
         .take_while(|e| e.is_some())
-        .collect::<Vec<Option<SigningPayload>>>();
-
-    let response = ConstructionPayloadsResponse {
-        unsigned_transaction,
-        payloads: signing_payloads,
-    };
-    Ok(Json(response))
+        .collect::<Vec<Option<SigningPayload>>>()
 }
 
 //Parse Unsigned Transaction to to Confirm Correctness
@@ -285,22 +805,12 @@ pub fn construction_parse(
     is_bad_network(&options, &construction_parse_request.network_identifier)?;
 
     let tx = get_tx_from_str(&construction_parse_request.transaction)?;
+    let signers = if construction_parse_request.signed {
+        get_signers(&tx, true)
+    } else {
+        vec![]
+    };
     let encoded_tx = EncodedTransaction::encode(tx, UiTransactionEncoding::JsonParsed);
-    let mut signers: Vec<AccountIdentifier> = vec![];
-    if construction_parse_request.signed {
-        if let EncodedTransaction::Json(t) = &encoded_tx {
-            if let UiMessage::Parsed(m) = &t.message {
-                m.account_keys.iter().for_each(|x| {
-                    if x.signer == true {
-                        signers.push(AccountIdentifier {
-                            address: x.pubkey.to_string(),
-                            sub_account: None,
-                        });
-                    }
-                });
-            }
-        }
-    }
     let account_identifier_signers = if signers.len() == 0 {
         None
     } else {
@@ -322,33 +832,86 @@ pub fn construction_combine(
 ) -> Result<Json<ConstructionCombineResponse>, ApiError> {
     is_bad_network(&options, &construction_combine_request.network_identifier)?;
 
-    let mut tx = get_tx_from_str(&construction_combine_request.unsigned_transaction)?;
-    let pubkeys = construction_combine_request
-        .signatures
-        .iter()
-        .map(|x| {
-            let p = hex::decode(&x.public_key.hex_bytes).unwrap();
-            Pubkey::new(&p)
-        })
-        .collect::<Vec<Pubkey>>();
-    let positions = tx
-        .get_signing_keypair_positions(pubkeys.as_slice())
-        .unwrap();
-    for i in 0..positions.len() {
-        tx.signatures[positions[i].unwrap()] = Signature::new(&hex::decode(
-            &construction_combine_request.signatures[i].hex_bytes,
-        )?);
-    }
-    let v = bincode::serialize(&tx);
-    if v.is_err() {
-        return Err(ApiError::BadTransactionPayload);
+    if construction_combine_request.signatures.is_empty() {
+        return Err(ApiError::BadSignatureCount);
     }
+    //unsigned_transaction can already be partially signed by other parties
+    //so missing signers are returned in pending_signers instead of BadSignatureCount
+    let (mut tx, encoding) = decode_tx(&construction_combine_request.unsigned_transaction)?;
+    verify_existing_signatures(&tx)?;
+    add_signatures(&mut tx, &construction_combine_request.signatures)?;
+    let pending = get_signers(&tx, false);
+    let pending_signers = if pending.is_empty() {
+        None
+    } else {
+        Some(pending)
+    };
     let response = ConstructionCombineResponse {
-        signed_transaction: bs58::encode(v.unwrap()).into_string(),
+        signed_transaction: encode_tx(&tx, encoding)?,
+        pending_signers,
     };
     Ok(Json(response))
 }
 
+//required signers whose signature is present or missing in tx
+fn get_signers(tx: &Transaction, signed: bool) -> Vec<AccountIdentifier> {
+    tx.signatures
+        .iter()
+        .zip(tx.message.account_keys.iter())
+        .filter(|(sig, _)| (**sig != Signature::default()) == signed)
+        .map(|(_, pubkey)| AccountIdentifier {
+            address: pubkey.to_string(),
+            sub_account: None,
+            metadata: None,
+        })
+        .collect()
+}
+//signatures already in a partially signed tx must be valid for its message
+fn verify_existing_signatures(tx: &Transaction) -> Result<(), ApiError> {
+    let message_data = tx.message_data();
+    let valid = tx
+        .signatures
+        .iter()
+        .zip(tx.message.account_keys.iter())
+        .filter(|(sig, _)| **sig != Signature::default())
+        .all(|(sig, pubkey)| sig.verify(pubkey.as_ref(), &message_data));
+    if !valid {
+        return Err(ApiError::BadSignature);
+    }
+    Ok(())
+}
+//verify each signature against message and put it in signer position
+fn add_signatures(
+    tx: &mut Transaction,
+    signatures: &[crate::types::Signature],
+) -> Result<(), ApiError> {
+    let message_data = tx.message_data();
+    for x in signatures {
+        if x.signature_type != SignatureType::Ed25519 {
+            return Err(ApiError::BadSignatureType);
+        }
+        if x.public_key.curve_type != CurveType::Edwards25519 {
+            return Err(ApiError::UnsupportedCurve);
+        }
+        let pubkey_bytes = hex::decode(&x.public_key.hex_bytes)?;
+        let signature_bytes = hex::decode(&x.hex_bytes)?;
+        if pubkey_bytes.len() != 32 || signature_bytes.len() != 64 {
+            return Err(ApiError::BadSignature);
+        }
+        let signature = Signature::new(&signature_bytes);
+        if !signature.verify(&pubkey_bytes, &message_data) {
+            return Err(ApiError::BadSignature);
+        }
+        //signature from a key which is not a required signer
+        let position = tx
+            .get_signing_keypair_positions(&[Pubkey::new(&pubkey_bytes)])
+            .map_err(|_| ApiError::InvalidSignedTransaction)?[0]
+            .ok_or(ApiError::BadSignature)?;
+        tx.signatures[position] = signature;
+    }
+    Ok(())
+}
+
 //broadcast signed tx
 
 pub fn construction_submit(
@@ -374,7 +937,9 @@ mod tests {
     use ed25519_dalek::*;
     use serde_json::json;
 
-    use crate::{consts, create_rpc_client, types::*};
+    use crate::{
+        consts, create_rpc_client, operations::stake::LockupMeta, types::*, utils::to_pub,
+    };
 
     //live debug tests on devnet
     //TODO: remove hardcoded keys
@@ -524,6 +1089,7 @@ mod tests {
                     account: Some(AccountIdentifier {
                         address: "95Dq3sXa3omVjiyxBSD6UMrzPYdmyu6CFCw5wS4rhqgV".to_string(),
                         sub_account: None,
+                        metadata: None,
                     }),
                     amount: Some(Amount {
                         value: "-0.01".to_string(),
@@ -548,6 +1114,7 @@ mod tests {
                     account: Some(AccountIdentifier {
                         address: "GyUjMMeZH3PVXp4tk5sR8LgnVaLTvCPipQ3dQY74k75L".to_string(),
                         sub_account: None,
+                        metadata: None,
                     }),
                     amount: Some(Amount {
                         value: "0.01".to_string(),
@@ -888,6 +1455,1276 @@ mod tests {
         );
     }
 
+    fn key() -> String {
+        Pubkey::new_unique().to_string()
+    }
+    fn offline_options() -> (Options, NetworkIdentifier) {
+        let options = Options {
+            rpc: create_rpc_client("https://devnet.solana.com".to_string()),
+            network: "devnet".to_string(),
+            nonce_accounts: vec![],
+            nonce_index: Default::default(),
+        };
+        let network_identifier = NetworkIdentifier {
+            blockchain: consts::BLOCKCHAIN.to_string(),
+            network: "devnet".to_string(),
+            sub_network_identifier: None,
+        };
+        (options, network_identifier)
+    }
+    //blockhash is all construction_payloads needs offline
+    fn offline_metadata() -> ConstructionMetadata {
+        serde_json::from_value::<ConstructionMetadata>(json!({
+            "blockhash": Hash::new_unique().to_string(),
+        }))
+        .unwrap()
+    }
+    //payloads of operations built offline and the parsed unsigned transaction
+    fn offline_payloads_and_parse(
+        operations: Vec<Operation>,
+    ) -> (ConstructionPayloadsResponse, ConstructionParseResponse) {
+        let (options, network_identifier) = offline_options();
+        let payloads = construction_payloads(
+            ConstructionPayloadsRequest {
+                network_identifier: network_identifier.clone(),
+                operations,
+                metadata: Some(offline_metadata()),
+            },
+            &options,
+        )
+        .unwrap()
+        .into_inner();
+        let parsed = construction_parse(
+            ConstructionParseRequest {
+                network_identifier,
+                signed: false,
+                transaction: payloads.unsigned_transaction.clone(),
+            },
+            &options,
+        )
+        .unwrap()
+        .into_inner();
+        (payloads, parsed)
+    }
+    fn operation(index: u64, type_: OperationType, metadata: serde_json::Value) -> Operation {
+        Operation {
+            operation_identifier: OperationIdentifier {
+                index,
+                network_index: None,
+            },
+            related_operations: None,
+            type_,
+            status: None,
+            account: None,
+            amount: None,
+            metadata: Some(metadata),
+        }
+    }
+    fn signature_for(keypair: &Keypair, msg: &str) -> crate::types::Signature {
+        crate::types::Signature {
+            signing_payload: SigningPayload {
+                hex_bytes: msg.to_string(),
+                account_identifier: None,
+                signature_type: Some(SignatureType::Ed25519),
+            },
+            public_key: crate::types::PublicKey {
+                hex_bytes: hex::encode(&keypair.public.as_bytes()),
+                curve_type: CurveType::Edwards25519,
+            },
+            signature_type: SignatureType::Ed25519,
+            hex_bytes: sign_msg(keypair, msg),
+        }
+    }
+
+    #[test]
+    fn test_combine_signatures() {
+        let (options, network_identifier) = offline_options();
+        let (k, p) = new_throwaway_signer();
+        let (k2, p2) = new_throwaway_signer();
+        let (k3, _) = new_throwaway_signer();
+        let instruction = solana_sdk::system_instruction::transfer(&p, &p2, 10);
+        let instruction2 = solana_sdk::system_instruction::transfer(&p2, &p, 10);
+        let tx = solana_sdk::transaction::Transaction::new_unsigned(Message::new(
+            &[instruction, instruction2],
+            Some(&p),
+        ));
+        let unsigned_transaction = encode_tx(&tx, Default::default()).unwrap();
+        let msg = hex::encode(tx.message_data());
+        let combine = |unsigned_transaction: &str, signatures| {
+            construction_combine(
+                ConstructionCombineRequest {
+                    network_identifier: network_identifier.clone(),
+                    unsigned_transaction: unsigned_transaction.to_string(),
+                    signatures,
+                },
+                &options,
+            )
+        };
+
+        let signed = combine(
+            &unsigned_transaction,
+            vec![signature_for(&k2, &msg), signature_for(&k, &msg)],
+        )
+        .unwrap();
+        assert!(signed.pending_signers.is_none());
+        assert!(get_tx_from_str(&signed.signed_transaction)
+            .unwrap()
+            .verify()
+            .is_ok());
+
+        let mut wrong_type = signature_for(&k, &msg);
+        wrong_type.signature_type = SignatureType::ECDSA;
+        assert!(matches!(
+            combine(&unsigned_transaction, vec![wrong_type]),
+            Err(ApiError::BadSignatureType)
+        ));
+        let mut wrong_sig = signature_for(&k, &msg);
+        wrong_sig.hex_bytes = sign_msg(&k, "00");
+        assert!(matches!(
+            combine(&unsigned_transaction, vec![wrong_sig]),
+            Err(ApiError::BadSignature)
+        ));
+        assert!(matches!(
+            combine(&unsigned_transaction, vec![signature_for(&k3, &msg)]),
+            Err(ApiError::BadSignature)
+        ));
+        assert!(matches!(
+            combine(&unsigned_transaction, vec![]),
+            Err(ApiError::BadSignatureCount)
+        ));
+    }
+
+    #[test]
+    fn test_partial_combine() {
+        let (options, network_identifier) = offline_options();
+        let (k, p) = new_throwaway_signer();
+        let (k2, p2) = new_throwaway_signer();
+        let instruction = solana_sdk::system_instruction::transfer(&p, &p2, 10);
+        let instruction2 = solana_sdk::system_instruction::transfer(&p2, &p, 10);
+        let tx = solana_sdk::transaction::Transaction::new_unsigned(Message::new(
+            &[instruction, instruction2],
+            Some(&p),
+        ));
+        let msg = hex::encode(tx.message_data());
+        let combine = |unsigned_transaction: String, signature| {
+            construction_combine(
+                ConstructionCombineRequest {
+                    network_identifier: network_identifier.clone(),
+                    unsigned_transaction,
+                    signatures: vec![signature],
+                },
+                &options,
+            )
+            .unwrap()
+            .into_inner()
+        };
+        let parse_signers = |transaction: String| {
+            construction_parse(
+                ConstructionParseRequest {
+                    network_identifier: network_identifier.clone(),
+                    signed: true,
+                    transaction,
+                },
+                &options,
+            )
+            .unwrap()
+            .into_inner()
+            .account_identifier_signers
+            .unwrap_or_default()
+            .into_iter()
+            .map(|x| x.address)
+            .collect::<Vec<String>>()
+        };
+
+        let partial = combine(
+            encode_tx(&tx, Default::default()).unwrap(),
+            signature_for(&k, &msg),
+        );
+        let pending = partial.pending_signers.clone().unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].address, p2.to_string());
+        assert_eq!(
+            parse_signers(partial.signed_transaction.clone()),
+            vec![p.to_string()]
+        );
+
+        let signed = combine(partial.signed_transaction, signature_for(&k2, &msg));
+        assert!(signed.pending_signers.is_none());
+        assert_eq!(
+            parse_signers(signed.signed_transaction.clone()),
+            vec![p.to_string(), p2.to_string()]
+        );
+        assert!(get_tx_from_str(&signed.signed_transaction)
+            .unwrap()
+            .verify()
+            .is_ok());
+
+        //signature of another message in the partially signed tx is rejected
+        let mut forged = tx;
+        forged.signatures[0] =
+            solana_sdk::signature::Signature::new(&k.sign(b"other message").to_bytes());
+        let result = construction_combine(
+            ConstructionCombineRequest {
+                network_identifier: network_identifier.clone(),
+                unsigned_transaction: encode_tx(&forged, Default::default()).unwrap(),
+                signatures: vec![signature_for(&k2, &msg)],
+            },
+            &options,
+        );
+        assert!(matches!(result, Err(ApiError::BadSignature)));
+    }
+
+    #[test]
+    fn test_derive_token_account() {
+        let (options, network_identifier) = offline_options();
+        let (k, p) = new_throwaway_signer();
+        let mint = "3fJRYbtSYZo9SYhwgUBn2zjG98ASy3kuUEnZeHJXqREr";
+        let derive = |metadata| {
+            construction_derive(
+                ConstructionDeriveRequest {
+                    network_identifier: network_identifier.clone(),
+                    public_key: crate::types::PublicKey {
+                        hex_bytes: hex::encode(k.public.as_bytes()),
+                        curve_type: CurveType::Edwards25519,
+                    },
+                    metadata,
+                },
+                &options,
+            )
+            .unwrap()
+            .into_inner()
+            .account_identifier
+        };
+        assert_eq!(derive(None).address, p.to_string());
+        let acc = derive(Some(ConstructionDeriveRequestMetadata {
+            mint: Some(mint.to_string()),
+            ..Default::default()
+        }));
+        assert_eq!(
+            acc.address,
+            get_associated_token_address(&p, &to_pub(mint)).to_string()
+        );
+        assert_eq!(acc.metadata.unwrap()["owner"], p.to_string());
+    }
+
+    #[test]
+    fn test_offline_nonce_payloads() {
+        let (options, network_identifier) = offline_options();
+        let (_, p) = new_throwaway_signer();
+        let (_, p2) = new_throwaway_signer();
+        let nonce_account = Pubkey::new_unique();
+        let blockhash = Hash::new_unique();
+        let operations = vec![operation(
+            0,
+            OperationType::System__Transfer,
+            json!({
+                "source": p.to_string(),
+                "destination": p2.to_string(),
+                "lamports": 10,
+            }),
+        )];
+        //only nonce data is given, no construction_metadata call
+        let metadata = serde_json::from_value::<ConstructionMetadata>(json!({
+            "with_nonce": {
+                "account": nonce_account.to_string(),
+                "authority": p.to_string(),
+                "blockhash": blockhash.to_string(),
+            }
+        }))
+        .unwrap();
+        let response = construction_payloads(
+            ConstructionPayloadsRequest {
+                network_identifier: network_identifier.clone(),
+                operations: operations.clone(),
+                metadata: Some(metadata),
+            },
+            &options,
+        )
+        .unwrap()
+        .into_inner();
+        let tx = get_tx_from_str(&response.unsigned_transaction).unwrap();
+        assert_eq!(tx.message.recent_blockhash, blockhash);
+        assert_eq!(tx.message.instructions.len(), 2);
+        let advance = &tx.message.instructions[0];
+        assert_eq!(
+            tx.message.account_keys[advance.program_id_index as usize],
+            system_program::id()
+        );
+        assert_eq!(
+            tx.message.account_keys[advance.accounts[0] as usize],
+            nonce_account
+        );
+        assert_eq!(response.payloads.len(), 1);
+
+        //destination doesn't sign the transfer so it can't be the nonce authority
+        let metadata = serde_json::from_value::<ConstructionMetadata>(json!({
+            "with_nonce": {
+                "account": nonce_account.to_string(),
+                "authority": p2.to_string(),
+                "blockhash": blockhash.to_string(),
+            }
+        }))
+        .unwrap();
+        let result = construction_payloads(
+            ConstructionPayloadsRequest {
+                network_identifier,
+                operations,
+                metadata: Some(metadata),
+            },
+            &options,
+        );
+        assert!(matches!(result, Err(ApiError::BadOperations(_))));
+    }
+
+    #[test]
+    fn test_nonce_account_lamports_missing() {
+        let (options, network_identifier) = offline_options();
+        let result = construction_payloads(
+            ConstructionPayloadsRequest {
+                network_identifier,
+                operations: vec![operation(
+                    0,
+                    OperationType::System__CreateNonceAccount,
+                    json!({
+                        "source": Pubkey::new_unique().to_string(),
+                        "destination": Pubkey::new_unique().to_string(),
+                        "authority": Pubkey::new_unique().to_string(),
+                    }),
+                )],
+                metadata: Some(offline_metadata()),
+            },
+            &options,
+        );
+        assert!(matches!(result, Err(ApiError::BadOperations(x)) if x == "lamports missing"));
+    }
+
+    #[test]
+    fn test_stake_account_lamports_missing() {
+        let (options, network_identifier) = offline_options();
+        let (source, destination) = (key(), key());
+        let operations = vec![
+            operation(
+                0,
+                OperationType::Stake__CreateAccount,
+                json!({"source": source, "destination": destination, "lockup": {}}),
+            ),
+            operation(
+                1,
+                OperationType::Stake__CreateAccountWithSeed,
+                json!({"source": source, "seed": "stake-1"}),
+            ),
+        ];
+        //lamports to stake are not filled by construction_metadata
+        for x in Matcher::new(&operations, None).combine().unwrap() {
+            let result = get_operation_metadata(&x, &CreatedAccounts::new(), &options);
+            assert!(matches!(result, Err(ApiError::BadOperations(x)) if x == "lamports missing"));
+        }
+        for x in operations {
+            let result = construction_payloads(
+                ConstructionPayloadsRequest {
+                    network_identifier: network_identifier.clone(),
+                    operations: vec![x],
+                    metadata: Some(offline_metadata()),
+                },
+                &options,
+            );
+            assert!(matches!(result, Err(ApiError::BadOperations(x)) if x == "lamports missing"));
+        }
+    }
+
+    #[test]
+    fn test_metadata_of_created_accounts() {
+        let (options, _) = offline_options();
+        let (mint, account, authority) = (key(), key(), key());
+        let operations = vec![
+            operation(
+                0,
+                OperationType::SplToken__CreateToken,
+                json!({"source": authority, "mint": mint, "amount": 1461600, "decimals": 6}),
+            ),
+            operation(
+                1,
+                OperationType::SplToken__CreateAccount,
+                json!({"source": authority, "destination": account, "mint": mint, "amount": 2039280}),
+            ),
+            operation(
+                2,
+                OperationType::SplToken__MintTo,
+                json!({"source": account, "mint": mint, "authority": authority, "amount": 10}),
+            ),
+            operation(
+                3,
+                OperationType::SplToken__MintToChecked,
+                json!({"source": account, "authority": authority, "amount": 10}),
+            ),
+        ];
+        let internal_operations = Matcher::new(&operations, None).combine().unwrap();
+        let mut created = CreatedAccounts::new();
+        for x in &internal_operations[..2] {
+            add_created_accounts(&mut created, x, &None);
+        }
+        //mint and account of the same request are not looked up on chain
+        let decimals =
+            |x: &InternalOperation| match get_operation_metadata(x, &created, &options).unwrap() {
+                Some(InternalOperationMetadata::SplToken(x)) => {
+                    assert_eq!(x.mint, Some(mint.clone()));
+                    x.decimals
+                }
+                _ => panic!("no token metadata"),
+            };
+        assert_eq!(decimals(&internal_operations[2]), None);
+        assert_eq!(decimals(&internal_operations[3]), Some(6));
+    }
+
+    #[test]
+    fn test_create_destination_on_transfer() {
+        let (_, p) = new_throwaway_signer();
+        let (_, p2) = new_throwaway_signer();
+        let mint = Pubkey::new_unique();
+        let source = get_associated_token_address(&p, &mint);
+        let destination = get_associated_token_address(&p2, &mint);
+        let operations = vec![operation(
+            0,
+            OperationType::SplToken__Transfer,
+            json!({
+                "source": source.to_string(),
+                "destination": destination.to_string(),
+                "authority": p.to_string(),
+                "mint": mint.to_string(),
+                "amount": 10,
+                "create_destination": true,
+                "destination_owner": p2.to_string(),
+            }),
+        )];
+        let (_, parsed) = offline_payloads_and_parse(operations);
+        let create = &parsed.operations[0];
+        assert_eq!(create.type_, OperationType::SplToken__CreateAssocAccount);
+        assert_eq!(create.account.as_ref().unwrap().address, p.to_string());
+        assert!(create.amount.is_none());
+        let meta = create.metadata.as_ref().unwrap();
+        assert_eq!(meta["destination"], destination.to_string());
+        assert_eq!(meta["owner"], p2.to_string());
+        assert!(meta.get("rent").is_none());
+        assert_eq!(
+            parsed.operations[1].type_,
+            OperationType::SplToken__Transfer
+        );
+    }
+
+    #[test]
+    fn test_create_destination_signers() {
+        let (owner, delegate, recipient) = (key(), key(), key());
+        let (multisig, signers) = (key(), vec![key(), key()]);
+        let mint = Pubkey::new_unique();
+        let source = key();
+        let destination = get_associated_token_address(&to_pub(&recipient), &mint).to_string();
+        let metadata = |authority: &str, extra: serde_json::Value| {
+            let mut metadata = json!({
+                "source": source,
+                "destination": destination,
+                "authority": authority,
+                "mint": mint.to_string(),
+                "amount": 10,
+                "create_destination": true,
+                "destination_owner": recipient,
+            });
+            for (k, v) in extra.as_object().unwrap() {
+                metadata[k] = v.clone();
+            }
+            metadata
+        };
+        //funder is the fee payer among the transfer signers, no other signer is added
+        let cases = vec![
+            (
+                metadata(&owner, json!({"delegate": delegate})),
+                delegate.clone(),
+                vec![delegate],
+            ),
+            (
+                metadata(&multisig, json!({"signers": signers})),
+                signers[0].clone(),
+                signers.clone(),
+            ),
+        ];
+        for (metadata, funder, expected_signers) in cases {
+            let (payloads, parsed) = offline_payloads_and_parse(vec![operation(
+                0,
+                OperationType::SplToken__Transfer,
+                metadata,
+            )]);
+            let payload_signers = payloads
+                .payloads
+                .iter()
+                .flatten()
+                .map(|x| x.account_identifier.as_ref().unwrap().address.clone())
+                .collect::<Vec<String>>();
+            assert_eq!(payload_signers, expected_signers);
+            let create = &parsed.operations[0];
+            assert_eq!(create.type_, OperationType::SplToken__CreateAssocAccount);
+            assert_eq!(create.account.as_ref().unwrap().address, funder);
+        }
+        //a later operation signed by another key makes it the fee payer and funder
+        let fee_payer = key();
+        let (payloads, parsed) = offline_payloads_and_parse(vec![
+            operation(
+                0,
+                OperationType::SplToken__Transfer,
+                metadata(&owner, json!({})),
+            ),
+            operation(
+                1,
+                OperationType::SplMemo__Memo,
+                json!({"memo": "deposit 1234", "source": fee_payer}),
+            ),
+        ]);
+        assert_eq!(
+            payloads.payloads[0]
+                .as_ref()
+                .unwrap()
+                .account_identifier
+                .as_ref()
+                .unwrap()
+                .address,
+            fee_payer
+        );
+        let create = &parsed.operations[0];
+        assert_eq!(create.type_, OperationType::SplToken__CreateAssocAccount);
+        assert_eq!(create.account.as_ref().unwrap().address, fee_payer);
+    }
+
+    #[test]
+    fn test_memo() {
+        let (_, p) = new_throwaway_signer();
+        let (_, p2) = new_throwaway_signer();
+        let operations = vec![
+            operation(
+                0,
+                OperationType::System__Transfer,
+                json!({
+                    "source": p.to_string(),
+                    "destination": p2.to_string(),
+                    "lamports": 10,
+                }),
+            ),
+            operation(
+                1,
+                OperationType::SplMemo__Memo,
+                json!({ "memo": "deposit 1234" }),
+            ),
+        ];
+        let (_, parsed) = offline_payloads_and_parse(operations);
+        let memo = parsed.operations.last().unwrap();
+        assert_eq!(memo.type_, OperationType::SplMemo__Memo);
+        assert_eq!(memo.metadata.as_ref().unwrap()["memo"], "deposit 1234");
+    }
+
+    #[test]
+    fn test_split_transactions() {
+        let (options, network_identifier) = offline_options();
+        let (_, p) = new_throwaway_signer();
+        let operations = (0..40)
+            .map(|i| {
+                operation(
+                    i,
+                    OperationType::System__Transfer,
+                    json!({
+                        "source": p.to_string(),
+                        "destination": Pubkey::new_unique().to_string(),
+                        "lamports": 10,
+                    }),
+                )
+            })
+            .collect::<Vec<Operation>>();
+        let payloads = |split| {
+            let metadata = serde_json::from_value::<ConstructionMetadata>(json!({
+                "blockhash": Hash::new_unique().to_string(),
+                "split": split,
+            }))
+            .unwrap();
+            construction_payloads(
+                ConstructionPayloadsRequest {
+                    network_identifier: network_identifier.clone(),
+                    operations: operations.clone(),
+                    metadata: Some(metadata),
+                },
+                &options,
+            )
+        };
+        assert!(matches!(
+            payloads(false),
+            Err(ApiError::TransactionTooLarge(_))
+        ));
+        let transactions = payloads(true).unwrap().into_inner().transactions.unwrap();
+        assert!(transactions.len() > 1);
+        let instructions = transactions
+            .iter()
+            .map(|x| {
+                let tx = get_tx_from_str(&x.unsigned_transaction).unwrap();
+                assert!(check_limits(&tx).is_ok());
+                tx.message.instructions.len()
+            })
+            .sum::<usize>();
+        assert_eq!(instructions, 40);
+    }
+
+    #[test]
+    fn test_split_destination_funder() {
+        let (options, network_identifier) = offline_options();
+        let (authority, p, q, recipient) = (key(), key(), key(), Pubkey::new_unique());
+        let mint = Pubkey::new_unique();
+        let transfer = |i, source: &str| {
+            operation(
+                i,
+                OperationType::System__Transfer,
+                json!({"source": source, "destination": key(), "lamports": 10}),
+            )
+        };
+        let mut operations = vec![operation(
+            0,
+            OperationType::SplToken__Transfer,
+            json!({
+                "source": get_associated_token_address(&to_pub(&authority), &mint).to_string(),
+                "destination": get_associated_token_address(&recipient, &mint).to_string(),
+                "authority": authority,
+                "mint": mint.to_string(),
+                "amount": 10,
+                "create_destination": true,
+                "destination_owner": recipient.to_string(),
+            }),
+        )];
+        operations.extend((1..41).map(|i| transfer(i, &p)));
+        //q pays the fee of the last transaction only
+        operations.push(transfer(41, &q));
+        let metadata = serde_json::from_value::<ConstructionMetadata>(json!({
+            "blockhash": Hash::new_unique().to_string(),
+            "split": true,
+        }))
+        .unwrap();
+        let transactions = construction_payloads(
+            ConstructionPayloadsRequest {
+                network_identifier: network_identifier.clone(),
+                operations,
+                metadata: Some(metadata),
+            },
+            &options,
+        )
+        .unwrap()
+        .into_inner()
+        .transactions
+        .unwrap();
+        assert!(transactions.len() > 1);
+        let first = &transactions[0];
+        let payload_signers = first
+            .payloads
+            .iter()
+            .flatten()
+            .map(|x| x.account_identifier.as_ref().unwrap().address.clone())
+            .collect::<Vec<String>>();
+        assert!(!payload_signers.contains(&q));
+        let parsed = construction_parse(
+            ConstructionParseRequest {
+                network_identifier,
+                signed: false,
+                transaction: first.unsigned_transaction.clone(),
+            },
+            &options,
+        )
+        .unwrap()
+        .into_inner();
+        let create = &parsed.operations[0];
+        assert_eq!(create.type_, OperationType::SplToken__CreateAssocAccount);
+        assert_eq!(create.account.as_ref().unwrap().address, p);
+    }
+
+    #[test]
+    fn test_multisig_transfer() {
+        let multisig = Pubkey::new_unique();
+        let signers = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+        let (source, destination) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let operations = vec![operation(
+            0,
+            OperationType::SplToken__Transfer,
+            json!({
+                "source": source.to_string(),
+                "destination": destination.to_string(),
+                "authority": multisig.to_string(),
+                "amount": 10,
+                "signers": signers.iter().map(|x| x.to_string()).collect::<Vec<String>>(),
+            }),
+        )];
+        let (payloads, parsed) = offline_payloads_and_parse(operations);
+        let payload_signers = payloads
+            .payloads
+            .iter()
+            .map(|x| x.clone().unwrap().account_identifier.unwrap().address)
+            .collect::<Vec<String>>();
+        assert_eq!(payload_signers.len(), 2);
+        assert!(signers
+            .iter()
+            .all(|x| payload_signers.contains(&x.to_string())));
+        let meta = parsed.operations[0].metadata.clone().unwrap();
+        assert_eq!(meta["authority"], multisig.to_string());
+        assert_eq!(meta["signers"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_delegate_transfer() {
+        let (owner, delegate) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (source, mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let metadata = json!({
+            "source": source.to_string(),
+            "destination": Pubkey::new_unique().to_string(),
+            "mint": mint.to_string(),
+            "authority": owner.to_string(),
+            "delegate": delegate.to_string(),
+            "amount": 10,
+        });
+        let (payloads, parsed) = offline_payloads_and_parse(vec![
+            operation(0, OperationType::SplToken__Transfer, metadata.clone()),
+            operation(1, OperationType::SplToken__Burn, metadata),
+        ]);
+        assert_eq!(payloads.payloads.len(), 1);
+        assert_eq!(
+            payloads.payloads[0]
+                .clone()
+                .unwrap()
+                .account_identifier
+                .unwrap()
+                .address,
+            delegate.to_string()
+        );
+        for x in parsed.operations {
+            assert_eq!(x.metadata.unwrap()["authority"], delegate.to_string());
+        }
+    }
+
+    #[test]
+    fn test_checked_token_operations() {
+        let (owner, new_authority) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (source, mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let metadata = |source: &Pubkey| {
+            json!({
+                "source": source.to_string(),
+                "destination": Pubkey::new_unique().to_string(),
+                "mint": mint.to_string(),
+                "authority": owner.to_string(),
+                "new_authority": new_authority.to_string(),
+                "authority_type": "mintTokens",
+                "amount": 10,
+                "decimals": 6,
+            })
+        };
+        let types = vec![
+            OperationType::SplToken__MintToChecked,
+            OperationType::SplToken__ApproveChecked,
+            OperationType::SplToken__BurnChecked,
+        ];
+        let mut operations = types
+            .iter()
+            .enumerate()
+            .map(|(i, x)| operation(i as u64, x.clone(), metadata(&source)))
+            .collect::<Vec<Operation>>();
+        operations.push(operation(
+            3,
+            OperationType::SplToken__SetAuthority,
+            metadata(&mint),
+        ));
+        let (_, parsed) = offline_payloads_and_parse(operations);
+        for (x, type_) in parsed.operations.iter().zip(types) {
+            assert_eq!(x.type_, type_);
+            assert_eq!(x.metadata.clone().unwrap()["tokenAmount"]["decimals"], 6);
+        }
+        let set_authority = &parsed.operations[3];
+        assert_eq!(set_authority.type_, OperationType::SplToken__SetAuthority);
+        let meta = set_authority.metadata.clone().unwrap();
+        assert_eq!(meta["mint"], mint.to_string());
+        assert_eq!(meta["newAuthority"], new_authority.to_string());
+        assert_eq!(meta["authorityType"], "mintTokens");
+    }
+
+    #[test]
+    fn test_malformed_delegate() {
+        let metadata = SplTokenOperationMetadata {
+            delegate: Some("not a pubkey".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            check_token_balance(&Account::default(), &metadata, &key()),
+            Err(ApiError::ParsePubkeyError(_))
+        ));
+    }
+
+    #[test]
+    fn test_stake_lockup() {
+        let custodian = Pubkey::new_unique();
+        let lockup = Lockup {
+            unix_timestamp: 0,
+            epoch: 100,
+            custodian,
+        };
+        let clock = |epoch| Clock {
+            epoch,
+            ..Default::default()
+        };
+        let stake = Pubkey::new_unique().to_string();
+        assert!(check_lockup(&lockup, &clock(101), None, &stake).is_ok());
+        assert!(check_lockup(&lockup, &clock(10), Some(&custodian), &stake).is_ok());
+        match check_lockup(&lockup, &clock(10), None, &stake) {
+            Err(ApiError::StakeLocked(x)) => assert!(x.contains(&custodian.to_string())),
+            _ => panic!("lockup not in force"),
+        }
+        assert_eq!(
+            Lockup::from(LockupMeta {
+                epoch: Some(100),
+                ..Default::default()
+            })
+            .custodian,
+            Pubkey::default()
+        );
+    }
+
+    #[test]
+    fn test_spl_token_round_trip() {
+        let (source, destination, mint) = (key(), key(), key());
+        let (owner, delegate, new_authority) = (key(), key(), key());
+        let signers = vec![key(), key()];
+        let assoc = get_associated_token_address(&to_pub(&owner), &to_pub(&mint)).to_string();
+        let token_amount = json!({"amount": "10", "decimals": 6, "uiAmount": 0.00001});
+        //operation, its metadata, parsed operation and the metadata it should have
+        let cases = vec![
+            (
+                OperationType::SplToken__InitializeMint,
+                json!({"source": owner, "mint": mint, "decimals": 6}),
+                OperationType::SplToken__InitializeMint,
+                json!({"mint": mint, "mintAuthority": owner, "decimals": 6}),
+            ),
+            (
+                OperationType::SplToken__InitializeAccount,
+                json!({"source": owner, "destination": destination, "mint": mint}),
+                OperationType::SplToken__InitializeAccount,
+                json!({"account": destination, "mint": mint, "owner": owner}),
+            ),
+            (
+                OperationType::SplToken__CreateToken,
+                json!({"source": owner, "mint": mint, "amount": 1461600, "decimals": 6}),
+                OperationType::SplToken__InitializeMint,
+                json!({"mint": mint, "mintAuthority": owner, "decimals": 6}),
+            ),
+            (
+                OperationType::SplToken__CreateAccount,
+                json!({"source": owner, "destination": destination, "mint": mint, "amount": 2039280}),
+                OperationType::SplToken__InitializeAccount,
+                json!({"account": destination, "mint": mint, "owner": owner}),
+            ),
+            (
+                OperationType::SplToken__CreateAssocAccount,
+                json!({"source": owner, "mint": mint}),
+                OperationType::SplToken__CreateAssocAccount,
+                json!({"source": owner, "destination": assoc, "owner": owner, "mint": mint}),
+            ),
+            (
+                OperationType::SplToken__Approve,
+                json!({"source": source, "destination": delegate, "authority": owner, "amount": 10}),
+                OperationType::SplToken__Approve,
+                json!({"source": source, "delegate": delegate, "owner": owner, "amount": "10"}),
+            ),
+            (
+                OperationType::SplToken__ApproveChecked,
+                json!({"source": source, "destination": delegate, "authority": owner, "mint": mint, "amount": 10, "decimals": 6}),
+                OperationType::SplToken__ApproveChecked,
+                json!({"source": source, "delegate": delegate, "owner": owner, "mint": mint, "tokenAmount": token_amount}),
+            ),
+            (
+                OperationType::SplToken__Revoke,
+                json!({"source": source, "authority": owner}),
+                OperationType::SplToken__Revoke,
+                json!({"source": source, "owner": owner}),
+            ),
+            (
+                OperationType::SplToken__SetAuthority,
+                json!({"source": mint, "authority": owner, "new_authority": new_authority, "authority_type": "freezeAccount"}),
+                OperationType::SplToken__SetAuthority,
+                json!({"mint": mint, "authority": owner, "newAuthority": new_authority, "authorityType": "freezeAccount"}),
+            ),
+            (
+                OperationType::SplToken__MintTo,
+                json!({"source": source, "mint": mint, "authority": owner, "amount": 10}),
+                OperationType::SplToken__MintTo,
+                json!({"account": source, "mint": mint, "mintAuthority": owner, "amount": "10"}),
+            ),
+            (
+                OperationType::SplToken__MintToChecked,
+                json!({"source": source, "mint": mint, "authority": owner, "amount": 10, "decimals": 6}),
+                OperationType::SplToken__MintToChecked,
+                json!({"account": source, "mint": mint, "mintAuthority": owner, "tokenAmount": token_amount}),
+            ),
+            (
+                OperationType::SplToken__Burn,
+                json!({"source": source, "mint": mint, "authority": owner, "amount": 10}),
+                OperationType::SplToken__Burn,
+                json!({"account": source, "mint": mint, "authority": owner, "amount": "10"}),
+            ),
+            (
+                OperationType::SplToken__Burn,
+                json!({"source": source, "mint": mint, "authority": owner, "delegate": delegate, "amount": 10}),
+                OperationType::SplToken__Burn,
+                json!({"account": source, "mint": mint, "authority": delegate, "amount": "10"}),
+            ),
+            (
+                OperationType::SplToken__BurnChecked,
+                json!({"source": source, "mint": mint, "authority": owner, "amount": 10, "decimals": 6}),
+                OperationType::SplToken__BurnChecked,
+                json!({"account": source, "mint": mint, "authority": owner, "tokenAmount": token_amount}),
+            ),
+            (
+                OperationType::SplToken__CloseAccount,
+                json!({"source": source, "destination": destination, "authority": owner}),
+                OperationType::SplToken__CloseAccount,
+                json!({"account": source, "destination": destination, "owner": owner}),
+            ),
+            (
+                OperationType::SplToken__CloseAccount,
+                json!({"source": source, "authority": owner}),
+                OperationType::SplToken__CloseAccount,
+                json!({"account": source, "destination": owner, "owner": owner}),
+            ),
+            (
+                OperationType::SplToken__FreezeAccount,
+                json!({"source": source, "mint": mint, "authority": owner}),
+                OperationType::SplToken__FreezeAccount,
+                json!({"account": source, "mint": mint, "freezeAuthority": owner}),
+            ),
+            (
+                OperationType::SplToken__ThawAccount,
+                json!({"source": source, "mint": mint, "authority": owner}),
+                OperationType::SplToken__ThawAccount,
+                json!({"account": source, "mint": mint, "freezeAuthority": owner}),
+            ),
+            (
+                OperationType::SplToken__InitializeMultisig,
+                json!({"source": source, "signers": signers, "m": 1}),
+                OperationType::SplToken__InitializeMultisig,
+                json!({"multisig": source, "signers": signers, "m": 1}),
+            ),
+            (
+                OperationType::SplToken__Transfer,
+                json!({"source": source, "destination": destination, "authority": owner, "amount": 10}),
+                OperationType::SplToken__Transfer,
+                json!({"authority": owner}),
+            ),
+            (
+                OperationType::SplToken__TransferChecked,
+                json!({"source": source, "destination": destination, "authority": owner, "mint": mint, "amount": 10, "decimals": 6}),
+                OperationType::SplToken__TransferChecked,
+                json!({"authority": owner, "mint": mint}),
+            ),
+        ];
+        for (type_, metadata, parsed_type, expected) in cases {
+            //memo signed by owner pays the fee when the operation has no signer
+            let (_, parsed) = offline_payloads_and_parse(vec![
+                operation(0, type_.clone(), metadata),
+                operation(
+                    1,
+                    OperationType::SplMemo__Memo,
+                    json!({"memo": "round trip", "source": owner}),
+                ),
+            ]);
+            let parsed_operation = parsed
+                .operations
+                .iter()
+                .find(|x| x.type_ == parsed_type)
+                .unwrap_or_else(|| panic!("{:?} not parsed", type_));
+            let parsed_metadata = parsed_operation.metadata.clone().unwrap();
+            for (k, v) in expected.as_object().unwrap() {
+                assert_eq!(&parsed_metadata[k], v, "{:?} {}", type_, k);
+            }
+            if type_.is_balance_changing() {
+                let (sender, receiver) = (&parsed.operations[0], &parsed.operations[1]);
+                assert_eq!(sender.account.as_ref().unwrap().address, source);
+                assert_eq!(sender.amount.as_ref().unwrap().value, "-10");
+                assert_eq!(receiver.account.as_ref().unwrap().address, destination);
+                assert_eq!(receiver.amount.as_ref().unwrap().value, "10");
+            }
+            if type_ == OperationType::SplToken__TransferChecked {
+                let currency = &parsed_operation.amount.as_ref().unwrap().currency;
+                assert_eq!(currency.symbol, mint);
+                assert_eq!(currency.decimals, 6);
+            }
+        }
+    }
+
+    #[test]
+    fn test_create_account_owner() {
+        let (source, account) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (_, parsed) = offline_payloads_and_parse(vec![operation(
+            0,
+            OperationType::System__CreateAccount,
+            json!({
+                "source": source.to_string(),
+                "destination": account.to_string(),
+                "lamports": 1000,
+                "space": 200,
+                "owner": solana_stake_program::id().to_string(),
+            }),
+        )]);
+        assert_eq!(
+            parsed.operations[0].metadata.clone().unwrap()["owner"],
+            solana_stake_program::id().to_string()
+        );
+    }
+
+    #[test]
+    fn test_transfer_with_seed() {
+        let (options, network_identifier) = offline_options();
+        let (k, base) = new_throwaway_signer();
+        let destination = Pubkey::new_unique();
+        let derived = construction_derive(
+            ConstructionDeriveRequest {
+                network_identifier,
+                public_key: crate::types::PublicKey {
+                    hex_bytes: hex::encode(k.public.as_bytes()),
+                    curve_type: CurveType::Edwards25519,
+                },
+                metadata: Some(ConstructionDeriveRequestMetadata {
+                    seed: Some("customer-1".to_string()),
+                    ..Default::default()
+                }),
+            },
+            &options,
+        )
+        .unwrap()
+        .into_inner()
+        .account_identifier;
+        let (_, parsed) = offline_payloads_and_parse(vec![operation(
+            0,
+            OperationType::System__TransferWithSeed,
+            json!({
+                "base": base.to_string(),
+                "seed": "customer-1",
+                "destination": destination.to_string(),
+                "lamports": 10,
+            }),
+        )]);
+        let sender = &parsed.operations[0];
+        assert_eq!(sender.type_, OperationType::System__TransferWithSeed);
+        assert_eq!(sender.account.as_ref().unwrap().address, derived.address);
+        let meta = sender.metadata.clone().unwrap();
+        assert_eq!(meta["base"], base.to_string());
+        assert_eq!(meta["seed"], "customer-1");
+        assert_eq!(
+            parsed.operations[1].account.as_ref().unwrap().address,
+            destination.to_string()
+        );
+    }
+
+    #[test]
+    fn test_seeded_account_mismatch() {
+        let (options, network_identifier) = offline_options();
+        let (base, other) = (Pubkey::new_unique(), key());
+        let derived =
+            Pubkey::create_with_seed(&base, "seed-1", &solana_sdk::system_program::id()).unwrap();
+        let cases = vec![
+            //base of the seeded source can't be guessed
+            (
+                OperationType::System__TransferWithSeed,
+                json!({
+                    "source": derived.to_string(),
+                    "seed": "seed-1",
+                    "destination": other,
+                    "lamports": 10,
+                }),
+                false,
+            ),
+            (
+                OperationType::System__AssignWithSeed,
+                json!({"source": other, "base": base.to_string(), "seed": "seed-1"}),
+                false,
+            ),
+            (
+                OperationType::System__AllocateWithSeed,
+                json!({
+                    "source": derived.to_string(),
+                    "base": base.to_string(),
+                    "seed": "seed-1",
+                    "space": 10,
+                }),
+                true,
+            ),
+            (
+                OperationType::System__CreateAccountWithSeed,
+                json!({
+                    "source": base.to_string(),
+                    "destination": other,
+                    "seed": "seed-1",
+                    "lamports": 10,
+                    "space": 0,
+                }),
+                false,
+            ),
+            (
+                OperationType::System__CreateAccountWithSeed,
+                json!({
+                    "source": base.to_string(),
+                    "destination": derived.to_string(),
+                    "seed": "seed-1",
+                    "lamports": 10,
+                    "space": 0,
+                }),
+                true,
+            ),
+        ];
+        for (type_, metadata, valid) in cases {
+            let result = construction_payloads(
+                ConstructionPayloadsRequest {
+                    network_identifier: network_identifier.clone(),
+                    operations: vec![operation(0, type_, metadata)],
+                    metadata: Some(offline_metadata()),
+                },
+                &options,
+            );
+            if valid {
+                assert!(result.is_ok());
+            } else {
+                assert!(matches!(result, Err(ApiError::BadOperations(_))));
+            }
+        }
+    }
+
+    #[test]
+    fn test_stake_with_seed() {
+        let (options, network_identifier) = offline_options();
+        let (k, base) = new_throwaway_signer();
+        let new_staker = Pubkey::new_unique();
+        let stake_account = construction_derive(
+            ConstructionDeriveRequest {
+                network_identifier,
+                public_key: crate::types::PublicKey {
+                    hex_bytes: hex::encode(k.public.as_bytes()),
+                    curve_type: CurveType::Edwards25519,
+                },
+                metadata: Some(ConstructionDeriveRequestMetadata {
+                    seed: Some("stake-1".to_string()),
+                    owner: Some(solana_stake_program::id().to_string()),
+                    ..Default::default()
+                }),
+            },
+            &options,
+        )
+        .unwrap()
+        .into_inner()
+        .account_identifier
+        .address;
+        let (payloads, parsed) = offline_payloads_and_parse(vec![
+            operation(
+                0,
+                OperationType::Stake__CreateAccountWithSeed,
+                json!({
+                    "source": base.to_string(),
+                    "seed": "stake-1",
+                    "lamports": 1000000000,
+                }),
+            ),
+            operation(
+                1,
+                OperationType::Stake__AuthorizeWithSeed,
+                json!({
+                    "stake_pubkey": stake_account,
+                    "base": base.to_string(),
+                    "seed": "stake-1",
+                    "owner": solana_stake_program::id().to_string(),
+                    "staker": new_staker.to_string(),
+                }),
+            ),
+        ]);
+        assert_eq!(payloads.payloads.len(), 1);
+        assert_eq!(
+            parsed.operations[0].type_,
+            OperationType::System__CreateAccountWithSeed
+        );
+        assert_eq!(
+            parsed.operations[1].account.as_ref().unwrap().address,
+            stake_account
+        );
+        let authorize = parsed.operations.last().unwrap();
+        assert_eq!(authorize.type_, OperationType::Stake__AuthorizeWithSeed);
+        let meta = authorize.metadata.clone().unwrap();
+        assert_eq!(meta["stakeAccount"], stake_account);
+        assert_eq!(meta["newAuthorized"], new_staker.to_string());
+        assert_eq!(meta["authoritySeed"], "stake-1");
+    }
+
+    #[test]
+    fn test_stake_with_seed_destination() {
+        let (options, network_identifier) = offline_options();
+        let base = Pubkey::new_unique();
+        let stake_account =
+            Pubkey::create_with_seed(&base, "stake-1", &solana_stake_program::id()).unwrap();
+        let create = |destination: String| {
+            operation(
+                0,
+                OperationType::Stake__CreateAccountWithSeed,
+                json!({
+                    "source": base.to_string(),
+                    "destination": destination,
+                    "seed": "stake-1",
+                    "lamports": 1000000000,
+                }),
+            )
+        };
+        let (_, parsed) = offline_payloads_and_parse(vec![create(stake_account.to_string())]);
+        assert_eq!(
+            parsed.operations[0].type_,
+            OperationType::System__CreateAccountWithSeed
+        );
+        assert_eq!(
+            parsed.operations[1].account.as_ref().unwrap().address,
+            stake_account.to_string()
+        );
+        //an account not derived from base and seed is not replaced by the derived one
+        let result = construction_payloads(
+            ConstructionPayloadsRequest {
+                network_identifier,
+                operations: vec![create(key())],
+                metadata: Some(offline_metadata()),
+            },
+            &options,
+        );
+        assert!(matches!(result, Err(ApiError::BadOperations(_))));
+    }
+
+    #[test]
+    fn test_vote_create_account() {
+        let source = Pubkey::new_unique();
+        let vote_account = Pubkey::new_unique();
+        let node = Pubkey::new_unique();
+        let withdrawer = Pubkey::new_unique();
+        let (payloads, parsed) = offline_payloads_and_parse(vec![operation(
+            0,
+            OperationType::Vote__CreateAccount,
+            json!({
+                "source": source.to_string(),
+                "destination": vote_account.to_string(),
+                "node_pubkey": node.to_string(),
+                "withdrawer": withdrawer.to_string(),
+                "comission": 7,
+                "lamports": 1000000,
+            }),
+        )]);
+        let signers = payloads
+            .payloads
+            .iter()
+            .flatten()
+            .map(|x| x.account_identifier.as_ref().unwrap().address.clone())
+            .collect::<Vec<String>>();
+        assert_eq!(signers.len(), 3);
+        assert!(signers.contains(&node.to_string()));
+        let meta = parsed.operations.last().unwrap().metadata.clone().unwrap();
+        assert_eq!(meta["node"], node.to_string());
+        assert_eq!(meta["authorizedVoter"], node.to_string());
+        assert_eq!(meta["authorizedWithdrawer"], withdrawer.to_string());
+        assert_eq!(meta["commission"], 7);
+    }
+
     fn constructions_pipe(
         operations: Vec<Operation>,
         mut keypairs: Vec<&Keypair>,
@@ -898,6 +2735,8 @@ mod tests {
         let options = Options {
             rpc: rpc,
             network: "devnet".to_string(),
+            nonce_accounts: vec![],
+            nonce_index: Default::default(),
         };
         let network_identifier = NetworkIdentifier {
             blockchain: consts::BLOCKCHAIN.to_string(),
@@ -907,9 +2746,12 @@ mod tests {
         let prepmeta = if let Some(x) = nonce {
             Some(ConstructionPreprocessRequestMetadata {
                 with_nonce: Some(WithNonce {
-                    account: x,
+                    account: Some(x),
                     authority: None,
+                    blockhash: None,
                 }),
+                encoding: None,
+                split: None,
             })
         } else {
             None
@@ -5,4 +5,10 @@ pub const NODE_VERSION: &str = "1.4.17";
 
 pub const NATIVE_SYMBOL: &str = "SOL";
 pub const NATIVE_DECIMALS: u8 = 9;
+//sub account holding staked balance of an authority
+pub const STAKE_SUB_ACCOUNT: &str = "stake";
+//max accounts a transaction can lock
+pub const MAX_TX_ACCOUNTS: usize = 64;
+//epochs of rewards fetched by /vote, two rpc calls each
+pub const MAX_REWARD_EPOCHS: usize = 10;
 pub const SEPARATOR: &str = "__"; //TODO: This should be only once in str or breaks
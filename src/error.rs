@@ -2,7 +2,10 @@ use std::io::Cursor;
 
 use crate::types::{self, ErrorDetails};
 use rocket::http::{hyper::StatusCode, Status};
-use solana_client::client_error::ClientError;
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind},
+    nonce_utils,
+};
 use solana_sdk::{
     program_error::ProgramError, pubkey::ParsePubkeyError, signature::ParseSignatureError,
 };
@@ -31,6 +34,20 @@ pub enum ApiError {
     BadOperations(String),
     #[error("account not found")]
     AccountNotFound,
+    #[error("transaction not found")]
+    TransactionNotFound,
+    #[error("nonce error: {0}")]
+    NonceError(nonce_utils::Error),
+    #[error("no nonce account available")]
+    NonceAccountUnavailable,
+    #[error("insufficient balance: {0}")]
+    InsufficientBalance(String),
+    #[error("transaction too large: {0}")]
+    TransactionTooLarge(String),
+    #[error("stake locked: {0}")]
+    StakeLocked(String),
+    #[error("transaction not in block: {0}")]
+    TransactionNotInBlock(String),
     #[error("system time error: {0:?}")]
     SystemTimeError(#[from] std::time::SystemTimeError),
     #[error("hex decoding failed: {0:?}")]
@@ -58,6 +75,17 @@ pub enum ApiError {
     #[error("Base64DecodeError")]
     Base64DecodeError(#[from] base64::DecodeError),
 }
+//rpc failures fetching a nonce account are client errors, a missing account is a bad nonce account
+impl From<nonce_utils::Error> for ApiError {
+    fn from(e: nonce_utils::Error) -> Self {
+        match e {
+            nonce_utils::Error::Client(x) if !x.starts_with("AccountNotFound") => {
+                ApiError::RpcClientError(ClientErrorKind::Custom(x).into())
+            }
+            e => ApiError::NonceError(e),
+        }
+    }
+}
 impl ApiError {
     pub fn code(&self) -> u64 {
         match self {
@@ -84,6 +112,13 @@ impl ApiError {
             ApiError::ParseSignatureError(_) => 200,
             ApiError::Base64DecodeError(_) => 210,
             ApiError::ProgramError(_) => 220,
+            ApiError::TransactionNotFound => 230,
+            ApiError::NonceError(_) => 240,
+            ApiError::NonceAccountUnavailable => 250,
+            ApiError::InsufficientBalance(_) => 260,
+            ApiError::TransactionTooLarge(_) => 270,
+            ApiError::StakeLocked(_) => 280,
+            ApiError::TransactionNotInBlock(_) => 290,
         }
     }
 
@@ -112,6 +147,13 @@ impl ApiError {
             ApiError::ParseSignatureError(_) => false,
             ApiError::Base64DecodeError(_) => false,
             ApiError::ProgramError(_) => false,
+            ApiError::TransactionNotFound => true,
+            ApiError::NonceError(_) => false,
+            ApiError::NonceAccountUnavailable => false,
+            ApiError::InsufficientBalance(_) => false,
+            ApiError::TransactionTooLarge(_) => false,
+            ApiError::StakeLocked(_) => false,
+            ApiError::TransactionNotInBlock(_) => false,
         }
     }
 
@@ -140,6 +182,13 @@ impl ApiError {
             ApiError::ParseSignatureError(_) => StatusCode::InternalServerError,
             ApiError::Base64DecodeError(_) => StatusCode::InternalServerError,
             ApiError::ProgramError(_) => StatusCode::InternalServerError,
+            ApiError::TransactionNotFound => StatusCode::NotFound,
+            ApiError::NonceError(_) => StatusCode::BadRequest,
+            ApiError::NonceAccountUnavailable => StatusCode::InternalServerError,
+            ApiError::InsufficientBalance(_) => StatusCode::BadRequest,
+            ApiError::TransactionTooLarge(_) => StatusCode::BadRequest,
+            ApiError::StakeLocked(_) => StatusCode::BadRequest,
+            ApiError::TransactionNotInBlock(_) => StatusCode::BadRequest,
         }
     }
 
@@ -250,6 +299,48 @@ impl ApiError {
                 retriable: false,
                 details: None,
             },
+            types::Error {
+                message: "transaction not found".to_string(),
+                code: 230,
+                retriable: true,
+                details: None,
+            },
+            types::Error {
+                message: "nonce error".to_string(),
+                code: 240,
+                retriable: false,
+                details: None,
+            },
+            types::Error {
+                message: "no nonce account available".to_string(),
+                code: 250,
+                retriable: false,
+                details: None,
+            },
+            types::Error {
+                message: "insufficient balance".to_string(),
+                code: 260,
+                retriable: false,
+                details: None,
+            },
+            types::Error {
+                message: "transaction too large".to_string(),
+                code: 270,
+                retriable: false,
+                details: None,
+            },
+            types::Error {
+                message: "stake locked".to_string(),
+                code: 280,
+                retriable: false,
+                details: None,
+            },
+            types::Error {
+                message: "transaction not in block".to_string(),
+                code: 290,
+                retriable: false,
+                details: None,
+            },
         ]
     }
 
@@ -11,11 +11,15 @@ mod consts;
 mod error;
 mod features;
 mod network;
+mod nonce;
 mod operations;
+mod payouts;
+mod stake;
 mod types;
 mod utils;
+mod vote;
 
-use std::{env, time::Duration};
+use std::{env, sync::atomic::AtomicUsize, time::Duration};
 
 use api_routes::construction::*;
 use api_routes::data::*;
@@ -32,6 +36,8 @@ use types::NetworkIdentifier;
 pub struct Options {
     rpc: RpcClient,
     network: String,
+    nonce_accounts: Vec<String>,
+    nonce_index: AtomicUsize,
 }
 pub struct Options2 {
     rpc2: RpcSender2,
@@ -74,8 +80,19 @@ fn main() {
     let host = env::var("HOST").unwrap_or("127.0.0.1".to_string());
     let port = env::var("PORT").unwrap_or("8080".to_string());
     let mode = env::var("MODE").unwrap_or("online".to_string());
+    let nonce_accounts = env::var("NONCE_ACCOUNTS")
+        .unwrap_or_default()
+        .split(',')
+        .filter(|x| !x.is_empty())
+        .map(|x| x.trim().to_string())
+        .collect();
     let rpc = create_rpc_client(rpc_url.clone());
-    let options = Options { rpc, network };
+    let options = Options {
+        rpc,
+        network,
+        nonce_accounts,
+        nonce_index: AtomicUsize::new(0),
+    };
 
     let config = Config::build(get_rocket_env())
         .keep_alive(0) //rosetta cli giving eof error if this is not disabled
@@ -93,7 +110,8 @@ fn main() {
             construction_payloads,
             construction_combine,
             construction_parse,
-            construction_hash
+            construction_hash,
+            construction_payouts
         ]
     } else {
         routes![
@@ -105,6 +123,9 @@ fn main() {
             block_transaction,
             call,
             features, //optional extra solana specific
+            nonce,
+            stake, //optional stake account state
+            vote,  //optional vote account state and rewards
             //TODO: offline paths are not disabled in online mode
             construction_combine,
             construction_derive,
@@ -114,6 +135,7 @@ fn main() {
             construction_payloads,
             construction_preprocess,
             construction_submit,
+            construction_payouts, //optional extra batch payouts
         ]
     };
 
@@ -0,0 +1,161 @@
+use std::sync::atomic::Ordering;
+
+use crate::{
+    error::ApiError,
+    is_bad_network,
+    types::{NonceAccount, NonceRequest, NonceResponse, WithNonce},
+    Options,
+};
+use rocket_contrib::json::Json;
+use solana_client::nonce_utils;
+use solana_sdk::{instruction::Instruction, nonce::state::Data, pubkey::Pubkey};
+
+//fetch nonce account and make sure its initialized
+pub fn get_nonce_data(account: &str, options: &Options) -> Result<Data, ApiError> {
+    let pubkey = account.parse::<Pubkey>()?;
+    let acc = nonce_utils::get_account(&options.rpc, &pubkey)?;
+    Ok(nonce_utils::data_from_account(&acc)?)
+}
+
+//accounts to try in order, round robin over the pool to spread transactions over the nonce accounts
+fn nonce_candidates(with_nonce: &WithNonce, pool: &[String], start: usize) -> Vec<String> {
+    if let Some(x) = &with_nonce.account {
+        return vec![x.clone()];
+    }
+    (0..pool.len())
+        .map(|i| pool[(start + i) % pool.len()].clone())
+        .collect()
+}
+
+//nonce authority signs the advance instruction and has to be a signer of the operations
+pub fn check_nonce_authority(
+    authority: &Pubkey,
+    instructions: &[Instruction],
+) -> Result<(), ApiError> {
+    let is_signer = instructions
+        .iter()
+        .flat_map(|x| x.accounts.iter())
+        .any(|x| x.is_signer && x.pubkey == *authority);
+    if !is_signer {
+        return Err(ApiError::BadOperations(format!(
+            "nonce authority {} is not a signer of the operations",
+            authority
+        )));
+    }
+    Ok(())
+}
+
+//resolve nonce account given in preprocess metadata or select one from NONCE_ACCOUNTS pool
+pub fn resolve_nonce(
+    with_nonce: &WithNonce,
+    options: &Options,
+) -> Result<(WithNonce, Data), ApiError> {
+    let authority = match &with_nonce.authority {
+        Some(x) => Some(x.parse::<Pubkey>()?),
+        None => None,
+    };
+    let start = if with_nonce.account.is_none() {
+        options.nonce_index.fetch_add(1, Ordering::Relaxed)
+    } else {
+        0
+    };
+    let accounts = nonce_candidates(with_nonce, &options.nonce_accounts, start);
+    let mut last_error = ApiError::NonceAccountUnavailable;
+    for account in accounts {
+        match get_nonce_data(&account, options) {
+            Ok(data) => {
+                if authority.is_some() && authority != Some(data.authority) {
+                    last_error = nonce_utils::Error::InvalidAuthority.into();
+                    continue;
+                }
+                let nonce = WithNonce {
+                    account: Some(account),
+                    authority: Some(data.authority.to_string()),
+                    blockhash: Some(data.blockhash.to_string()),
+                };
+                return Ok((nonce, data));
+            }
+            Err(e) => last_error = e,
+        }
+    }
+    if with_nonce.account.is_none() {
+        return Err(ApiError::NonceAccountUnavailable);
+    }
+    Err(last_error)
+}
+
+pub fn nonce_accounts(
+    nonce_request: NonceRequest,
+    options: &Options,
+) -> Result<Json<NonceResponse>, ApiError> {
+    is_bad_network(options, &nonce_request.network_identifier)?;
+
+    let accounts = nonce_request
+        .accounts
+        .unwrap_or_else(|| options.nonce_accounts.clone())
+        .iter()
+        .map(|x| {
+            let data = get_nonce_data(x, options)?;
+            Ok(NonceAccount {
+                address: x.clone(),
+                authority: data.authority.to_string(),
+                blockhash: data.blockhash.to_string(),
+                lamports_per_signature: data.fee_calculator.lamports_per_signature,
+            })
+        })
+        .collect::<Result<Vec<NonceAccount>, ApiError>>()?;
+    Ok(Json(NonceResponse { accounts }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::system_instruction;
+
+    #[test]
+    fn nonce_candidates_test() {
+        let pool = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        let from_pool = WithNonce {
+            account: None,
+            authority: None,
+            blockhash: None,
+        };
+        assert_eq!(nonce_candidates(&from_pool, &pool, 0), ["a", "b", "c"]);
+        assert_eq!(nonce_candidates(&from_pool, &pool, 1), ["b", "c", "a"]);
+        assert_eq!(nonce_candidates(&from_pool, &pool, 5), ["c", "a", "b"]);
+        assert!(nonce_candidates(&from_pool, &[], 3).is_empty());
+        let given = WithNonce {
+            account: Some("d".to_string()),
+            ..from_pool
+        };
+        assert_eq!(nonce_candidates(&given, &pool, 1), ["d"]);
+    }
+
+    #[test]
+    fn nonce_error_test() {
+        let client = ApiError::from(nonce_utils::Error::Client("connection refused".to_string()));
+        assert!(matches!(client, ApiError::RpcClientError(_)));
+        let missing = ApiError::from(nonce_utils::Error::Client(format!(
+            "AccountNotFound: pubkey={}",
+            Pubkey::new_unique()
+        )));
+        assert!(matches!(missing, ApiError::NonceError(_)));
+        assert!(!missing.retriable());
+        let invalid = ApiError::from(nonce_utils::Error::InvalidAuthority);
+        assert!(matches!(invalid, ApiError::NonceError(_)));
+        assert!(!invalid.retriable());
+    }
+
+    #[test]
+    fn check_nonce_authority_test() {
+        let (source, destination) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let instructions = vec![system_instruction::transfer(&source, &destination, 10)];
+        assert!(check_nonce_authority(&source, &instructions).is_ok());
+        //destination is in the instruction but doesn't sign
+        assert!(matches!(
+            check_nonce_authority(&destination, &instructions),
+            Err(ApiError::BadOperations(_))
+        ));
+        assert!(check_nonce_authority(&Pubkey::new_unique(), &instructions).is_err());
+    }
+}
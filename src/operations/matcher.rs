@@ -1,11 +1,14 @@
 use merge::Merge;
 use serde_json::Value;
-use solana_sdk::instruction::Instruction;
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey};
+use spl_associated_token_account::get_associated_token_address;
 
+use super::get_fee_payer;
+use super::memo::*;
 use super::stake::*;
 use super::system::*;
 use super::vote::*;
-use super::{spltoken, spltoken::*, stake, system, vote};
+use super::{memo, spltoken, spltoken::*, stake, system, vote};
 use crate::{
     error::ApiError,
     merge_meta, set_meta,
@@ -19,6 +22,7 @@ pub enum InternalOperationMetadata {
     Vote(VoteOperationMetadata),
     Stake(StakeOperationMetadata),
     SplToken(SplTokenOperationMetadata),
+    Memo(MemoOperationMetadata),
 }
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct InternalOperation {
@@ -32,8 +36,69 @@ impl InternalOperation {
             InternalOperationMetadata::Vote(x) => vote::to_instruction(self.type_, x)?,
             InternalOperationMetadata::Stake(x) => stake::to_instruction(self.type_, x)?,
             InternalOperationMetadata::SplToken(x) => spltoken::to_instruction(self.type_, x)?,
+            InternalOperationMetadata::Memo(x) => memo::to_instruction(self.type_, x)?,
         })
     }
+    //fill values missing from operation metadata with the ones from construction_metadata
+    pub fn merge_metadata(&mut self, meta: &Option<InternalOperationMetadata>) {
+        match (&mut self.metadata, meta) {
+            (InternalOperationMetadata::System(x), Some(InternalOperationMetadata::System(y))) => {
+                x.merge(y.clone())
+            }
+            (InternalOperationMetadata::Vote(x), Some(InternalOperationMetadata::Vote(y))) => {
+                x.merge(y.clone())
+            }
+            (InternalOperationMetadata::Stake(x), Some(InternalOperationMetadata::Stake(y))) => {
+                x.merge(y.clone())
+            }
+            (
+                InternalOperationMetadata::SplToken(x),
+                Some(InternalOperationMetadata::SplToken(y)),
+            ) => x.merge(y.clone()),
+            (InternalOperationMetadata::Memo(x), Some(InternalOperationMetadata::Memo(y))) => {
+                x.merge(y.clone())
+            }
+            _ => {}
+        }
+    }
+}
+//instructions of each operation, kept together when transactions are split
+//associated token accounts created for transfers are funded by the fee payer
+pub fn to_instruction_groups(
+    operations: Vec<InternalOperation>,
+) -> Result<Vec<Vec<Instruction>>, ApiError> {
+    let mut destinations = vec![];
+    let mut groups = vec![];
+    for x in operations {
+        destinations.push(match &x.metadata {
+            InternalOperationMetadata::SplToken(m)
+                if x.type_ == OperationType::SplToken__Transfer
+                    || x.type_ == OperationType::SplToken__TransferChecked =>
+            {
+                Some(m.clone())
+            }
+            _ => None,
+        });
+        groups.push(x.to_instruction()?);
+    }
+    let fee_payer = get_fee_payer(&groups.concat());
+    for (group, destination) in groups.iter_mut().zip(destinations) {
+        if let Some(m) = destination {
+            if let Some(x) = spltoken::create_destination_instruction(&m, fee_payer.as_ref())? {
+                group.insert(0, x);
+            }
+        }
+    }
+    Ok(groups)
+}
+//SPL operations with resolve_wallets metadata use owner wallets instead of token accounts
+fn resolve_wallets(operation: &Operation) -> bool {
+    operation
+        .metadata
+        .as_ref()
+        .and_then(|x| x.get("resolve_wallets"))
+        .and_then(|x| x.as_bool())
+        .unwrap_or(false)
 }
 pub struct Matcher<'a> {
     checked_indexes: Vec<u64>,
@@ -49,15 +114,6 @@ impl<'a> Matcher<'a> {
             meta,
         }
     }
-    pub fn to_instructions(&mut self) -> Result<Vec<Instruction>, ApiError> {
-        let combined = self.combine()?;
-        Ok(combined
-            .into_iter()
-            .map(|x| x.to_instruction().unwrap())
-            .flatten()
-            .collect())
-    }
-
     pub fn combine(&mut self) -> Result<Vec<InternalOperation>, ApiError> {
         let mut internal_operations = vec![];
         for i in 0..self.operations.len() {
@@ -100,7 +156,7 @@ impl<'a> Matcher<'a> {
                             let address = matched_op.address();
 
                             //if subamt is -ve then subamt is sender is source
-                            let (source, destination, lamports) = if amount < 0.0 {
+                            let (mut source, mut destination, lamports) = if amount < 0.0 {
                                 //negative = this is sender
                                 (address, main_address, main_amount as u64)
                             } else {
@@ -111,6 +167,41 @@ impl<'a> Matcher<'a> {
                             }
                             if let Some(ref mut m) = meta_clone {
                                 if let Value::Object(ref mut map) = m {
+                                    //accounts are owner wallets, currency symbol is the mint
+                                    if operation.type_.is_spl_token()
+                                        && (resolve_wallets(&operation)
+                                            || resolve_wallets(matched_op))
+                                    {
+                                        let mint = amt.currency.symbol.parse::<Pubkey>()?;
+                                        if map.get("authority").is_none() {
+                                            map.insert(
+                                                "authority".to_string(),
+                                                Value::String(source.clone()),
+                                            );
+                                        }
+                                        map.insert(
+                                            "destination_owner".to_string(),
+                                            Value::String(destination.clone()),
+                                        );
+                                        map.insert(
+                                            "mint".to_string(),
+                                            Value::String(mint.to_string()),
+                                        );
+                                        map.insert(
+                                            "decimals".to_string(),
+                                            Value::from(amt.currency.decimals),
+                                        );
+                                        source = get_associated_token_address(
+                                            &source.parse::<Pubkey>()?,
+                                            &mint,
+                                        )
+                                        .to_string();
+                                        destination = get_associated_token_address(
+                                            &destination.parse::<Pubkey>()?,
+                                            &mint,
+                                        )
+                                        .to_string();
+                                    }
                                     map.insert(
                                         "source".to_string(),
                                         serde_json::Value::String(source.clone()),
@@ -141,10 +232,17 @@ impl<'a> Matcher<'a> {
                         }
                         if let Some(ref mut m) = meta_clone {
                             if let Value::Object(ref mut map) = m {
-                                map.insert(
-                                    "source".to_string(),
-                                    serde_json::Value::String(acc.address.clone()),
-                                );
+                                let mut source = acc.address.clone();
+                                if operation.type_.is_spl_token() && resolve_wallets(&operation) {
+                                    if let Some(Value::String(mint)) = map.get("mint") {
+                                        source = get_associated_token_address(
+                                            &acc.address.parse::<Pubkey>()?,
+                                            &mint.parse::<Pubkey>()?,
+                                        )
+                                        .to_string();
+                                    }
+                                }
+                                map.insert("source".to_string(), serde_json::Value::String(source));
                                 if map.get("authority").is_none() {
                                     map.insert(
                                         "authority".to_string(),
@@ -158,7 +256,9 @@ impl<'a> Matcher<'a> {
                 if let Some(ref mut m) = meta_clone {
                     if let Value::Object(ref mut map) = m {
                         if map.get("authority").is_none() {
-                            map.insert("authority".to_string(), map.get("source").unwrap().clone());
+                            if let Some(source) = map.get("source").cloned() {
+                                map.insert("authority".to_string(), source);
+                            }
                         }
                     }
                 }
@@ -170,7 +270,11 @@ impl<'a> Matcher<'a> {
                     | OperationType::System__CreateNonceAccount
                     | OperationType::System__AdvanceNonce
                     | OperationType::System__WithdrawFromNonce
-                    | OperationType::System__AuthorizeNonce => {
+                    | OperationType::System__AuthorizeNonce
+                    | OperationType::System__CreateAccountWithSeed
+                    | OperationType::System__TransferWithSeed
+                    | OperationType::System__AllocateWithSeed
+                    | OperationType::System__AssignWithSeed => {
                         let mut new_metadata = set_meta!(meta_clone, SystemOperationMetadata);
                         merge_meta!(new_metadata, &self.meta, internal_operations.len(), System);
 
@@ -192,7 +296,12 @@ impl<'a> Matcher<'a> {
                     | OperationType::SplToken__FreezeAccount
                     | OperationType::SplToken__ThawAccount
                     | OperationType::SplToken__TransferChecked
-                    | OperationType::SplToken__CreateAssocAccount => {
+                    | OperationType::SplToken__CreateAssocAccount
+                    | OperationType::SplToken__InitializeMultisig
+                    | OperationType::SplToken__SetAuthority
+                    | OperationType::SplToken__ApproveChecked
+                    | OperationType::SplToken__MintToChecked
+                    | OperationType::SplToken__BurnChecked => {
                         let mut new_metadata = set_meta!(meta_clone, SplTokenOperationMetadata);
                         merge_meta!(
                             new_metadata,
@@ -207,10 +316,12 @@ impl<'a> Matcher<'a> {
                         })
                     }
                     OperationType::Stake__CreateAccount
+                    | OperationType::Stake__CreateAccountWithSeed
                     | OperationType::Stake__Delegate
                     | OperationType::Stake__Split
                     | OperationType::Stake__Merge
                     | OperationType::Stake__Authorize
+                    | OperationType::Stake__AuthorizeWithSeed
                     | OperationType::Stake__Withdraw
                     | OperationType::Stake__Deactivate
                     | OperationType::Stake__SetLockup => {
@@ -233,6 +344,14 @@ impl<'a> Matcher<'a> {
                             metadata: InternalOperationMetadata::Vote(new_metadata),
                         })
                     }
+                    OperationType::SplMemo__Memo => {
+                        let mut new_metadata = set_meta!(meta_clone, MemoOperationMetadata);
+                        merge_meta!(new_metadata, &self.meta, internal_operations.len(), Memo);
+                        internal_operations.push(InternalOperation {
+                            type_: operation.type_,
+                            metadata: InternalOperationMetadata::Memo(new_metadata),
+                        })
+                    }
                     OperationType::Unknown => {}
                 };
             }
@@ -247,7 +366,9 @@ mod tests {
     use crate::types::*;
     use serde_json::json;
 
-    use super::Matcher;
+    use super::{InternalOperationMetadata, Matcher};
+    use solana_sdk::pubkey::Pubkey;
+    use spl_associated_token_account::get_associated_token_address;
 
     #[test]
     fn convert_op_test() {
@@ -262,6 +383,7 @@ mod tests {
                 account: Some(AccountIdentifier {
                     address: "SenderAddress".to_string(),
                     sub_account: None,
+                    metadata: None,
                 }),
                 amount: Some(Amount {
                     value: "-1000".to_string(),
@@ -284,6 +406,7 @@ mod tests {
                 account: Some(AccountIdentifier {
                     address: "DestinationAddress".to_string(),
                     sub_account: None,
+                    metadata: None,
                 }),
                 amount: Some(Amount {
                     value: "1000".to_string(),
@@ -323,6 +446,7 @@ mod tests {
                 account: Some(AccountIdentifier {
                     address: "SS".to_string(),
                     sub_account: None,
+                    metadata: None,
                 }),
                 amount: Some(Amount {
                     value: "-10".to_string(),
@@ -347,6 +471,7 @@ mod tests {
                 account: Some(AccountIdentifier {
                     address: "DD".to_string(),
                     sub_account: None,
+                    metadata: None,
                 }),
                 amount: Some(Amount {
                     value: "10".to_string(),
@@ -367,4 +492,52 @@ mod tests {
         assert_eq!(ops.len(), 3);
         println!("{:?}", ops);
     }
+
+    #[test]
+    fn resolve_wallets_test() {
+        let mint = Pubkey::new_unique();
+        let sender = Pubkey::new_unique();
+        let receiver = Pubkey::new_unique();
+        let op = |index, address: &Pubkey, value: &str| Operation {
+            operation_identifier: OperationIdentifier {
+                index,
+                network_index: None,
+            },
+            related_operations: None,
+            status: None,
+            account: Some(AccountIdentifier {
+                address: address.to_string(),
+                sub_account: None,
+                metadata: None,
+            }),
+            amount: Some(Amount {
+                value: value.to_string(),
+                currency: Currency {
+                    symbol: mint.to_string(),
+                    decimals: 2,
+                    metadata: None,
+                },
+            }),
+            type_: OperationType::SplToken__TransferChecked,
+            metadata: Some(json!({ "resolve_wallets": true })),
+        };
+        let operations = vec![op(0, &sender, "-10"), op(1, &receiver, "10")];
+        let ops = Matcher::new(&operations, None).combine().unwrap();
+        assert_eq!(ops.len(), 1);
+        if let InternalOperationMetadata::SplToken(x) = &ops[0].metadata {
+            assert_eq!(
+                x.source,
+                Some(get_associated_token_address(&sender, &mint).to_string())
+            );
+            assert_eq!(
+                x.destination,
+                Some(get_associated_token_address(&receiver, &mint).to_string())
+            );
+            assert_eq!(x.authority, Some(sender.to_string()));
+            assert_eq!(x.destination_owner, Some(receiver.to_string()));
+            assert_eq!(x.decimals, Some(2));
+        } else {
+            panic!("not a token operation");
+        }
+    }
 }
@@ -0,0 +1,37 @@
+use crate::{error::ApiError, types::OperationType, utils::to_pub};
+use merge::Merge;
+use serde::{Deserialize, Serialize};
+use solana_sdk::instruction::{AccountMeta, Instruction};
+
+#[derive(Merge, Default, Clone, Debug, Deserialize, Serialize)]
+pub struct MemoOperationMetadata {
+    pub memo: Option<String>,
+    ///optional signer of the memo
+    pub source: Option<String>,
+}
+pub fn to_instruction(
+    type_: OperationType,
+    metadata: MemoOperationMetadata,
+) -> Result<Vec<Instruction>, ApiError> {
+    let instructions = match type_ {
+        OperationType::SplMemo__Memo => {
+            let memo = metadata
+                .memo
+                .ok_or_else(|| ApiError::BadOperations("memo missing".to_string()))?;
+            let accounts = if let Some(x) = &metadata.source {
+                vec![AccountMeta::new_readonly(to_pub(x), true)]
+            } else {
+                vec![]
+            };
+            vec![Instruction {
+                program_id: spl_memo::id(),
+                accounts,
+                data: memo.into_bytes(),
+            }]
+        }
+        _ => {
+            return Err(ApiError::BadOperations("Invalid Operation".to_string()));
+        }
+    };
+    Ok(instructions)
+}
@@ -1,4 +1,5 @@
 pub mod matcher;
+pub mod memo;
 pub mod spltoken;
 pub mod stake;
 pub mod system;
@@ -7,11 +8,14 @@ pub mod vote;
 
 use crate::{
     error::ApiError,
-    types::{Operation, OptionalInternalOperationMetadatas},
+    types::{Operation, OptionalInternalOperationMetadatas, TransactionEncoding},
 };
 
 use serde::{Deserialize, Serialize};
-use solana_sdk::{message::Message, transaction::Transaction};
+use solana_sdk::{
+    instruction::Instruction, message::Message, pubkey::Pubkey, sanitize::Sanitize,
+    transaction::Transaction,
+};
 
 use self::matcher::Matcher;
 
@@ -24,18 +28,80 @@ impl Operation {
     }
 }
 
+//first signer of the last instruction signed by anyone pays the transaction fee
+pub fn get_fee_payer(instructions: &[Instruction]) -> Option<Pubkey> {
+    instructions
+        .iter()
+        .rev()
+        .find_map(|x| x.accounts.iter().find(|a| a.is_signer))
+        .map(|x| x.pubkey)
+}
 pub fn get_tx_from_str(s: &str) -> Result<Transaction, ApiError> {
-    let try_bs58 = bs58::decode(&s).into_vec().unwrap_or(vec![]);
-    if try_bs58.len() == 0 {
-        return Err(ApiError::InvalidSignedTransaction);
+    let (tx, _) = decode_tx(s)?;
+    Ok(tx)
+}
+///Detects hex, base58 or base64 encoded transaction and returns it with the encoding used
+pub fn decode_tx(s: &str) -> Result<(Transaction, TransactionEncoding), ApiError> {
+    let is_hex = s.len() % 2 == 0 && s.chars().all(|c| c.is_ascii_hexdigit());
+    let mut encodings = vec![TransactionEncoding::Base58, TransactionEncoding::Base64];
+    if is_hex {
+        encodings.insert(0, TransactionEncoding::Hex);
     }
-    let data = try_bs58;
-    /*
-    let try_base64 = base64::decode(&s);
-    let data = if try_base64.is_err() {
-    } else {
-        try_base64.unwrap()
+    for encoding in encodings {
+        let data = match encoding {
+            TransactionEncoding::Hex => hex::decode(s).ok(),
+            TransactionEncoding::Base58 => bs58::decode(s).into_vec().ok(),
+            TransactionEncoding::Base64 => base64::decode(s).ok(),
+        };
+        //sanitize makes sure there is a signature for every required signer
+        if let Some(data) = data {
+            if let Ok(tx) = bincode::deserialize::<Transaction>(&data) {
+                if tx.sanitize().is_ok() {
+                    return Ok((tx, encoding));
+                }
+            }
+        }
+    }
+    Err(ApiError::InvalidSignedTransaction)
+}
+pub fn encode_tx(tx: &Transaction, encoding: TransactionEncoding) -> Result<String, ApiError> {
+    let data = bincode::serialize(tx).map_err(|_| ApiError::BadTransactionPayload)?;
+    Ok(match encoding {
+        TransactionEncoding::Base58 => bs58::encode(data).into_string(),
+        TransactionEncoding::Base64 => base64::encode(data),
+        TransactionEncoding::Hex => hex::encode(data),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::{
+        hash::Hash, message::Message, pubkey::Pubkey, system_instruction, transaction::Transaction,
     };
-    */
-    Ok(bincode::deserialize(&data).unwrap())
+
+    use super::*;
+
+    #[test]
+    fn tx_encoding_test() {
+        let from = Pubkey::new_unique();
+        let instruction = system_instruction::transfer(&from, &Pubkey::new_unique(), 100);
+        let mut tx = Transaction::new_unsigned(Message::new(&[instruction], Some(&from)));
+        tx.message.recent_blockhash = Hash::new_unique();
+        for encoding in vec![
+            TransactionEncoding::Base58,
+            TransactionEncoding::Base64,
+            TransactionEncoding::Hex,
+        ] {
+            let s = encode_tx(&tx, encoding).unwrap();
+            let (decoded, detected) = decode_tx(&s).unwrap();
+            assert_eq!(decoded, tx);
+            assert_eq!(detected, encoding);
+        }
+        assert!(decode_tx("invalid").is_err());
+        tx.signatures.clear();
+        assert!(matches!(
+            decode_tx(&encode_tx(&tx, TransactionEncoding::Base58).unwrap()),
+            Err(ApiError::InvalidSignedTransaction)
+        ));
+    }
 }
@@ -1,8 +1,10 @@
 use crate::{error::ApiError, types::OperationType, utils::to_pub};
 use merge::Merge;
 use serde::{Deserialize, Serialize};
-use solana_sdk::{instruction::Instruction, program_pack::Pack, system_instruction};
-use spl_token::state::Mint;
+use solana_sdk::{
+    instruction::Instruction, program_pack::Pack, pubkey::Pubkey, system_instruction,
+};
+use spl_token::{instruction::AuthorityType, state::Mint};
 
 const MIN_RENT: u64 = 100000;
 #[derive(Merge, Default, Clone, Debug, Deserialize, Serialize)]
@@ -14,6 +16,62 @@ pub struct SplTokenOperationMetadata {
     pub freeze_authority: Option<String>,
     pub amount: Option<u64>,
     pub decimals: Option<u8>,
+    ///create associated token account of destination_owner before transfer if missing
+    pub create_destination: Option<bool>,
+    pub destination_owner: Option<String>,
+    ///set by construction_metadata
+    pub destination_exists: Option<bool>,
+    ///signers of multisig authority
+    pub signers: Option<Vec<String>>,
+    ///number of signers required by multisig
+    #[serde(alias = "m")]
+    pub required_signers: Option<u8>,
+    ///approved delegate signing transfers and burns instead of the owner
+    pub delegate: Option<String>,
+    ///pays for accounts created with the operation, fee payer of the transaction by default
+    pub payer: Option<String>,
+    ///authority set by SetAuthority, authority is removed if missing
+    pub new_authority: Option<String>,
+    ///mintTokens, freezeAccount, accountOwner or closeAccount
+    pub authority_type: Option<String>,
+}
+
+fn to_authority_type(s: &str) -> Result<AuthorityType, ApiError> {
+    match s {
+        "mintTokens" => Ok(AuthorityType::MintTokens),
+        "freezeAccount" => Ok(AuthorityType::FreezeAccount),
+        "accountOwner" => Ok(AuthorityType::AccountOwner),
+        "closeAccount" => Ok(AuthorityType::CloseAccount),
+        _ => Err(ApiError::BadOperations(format!("bad authority_type {}", s))),
+    }
+}
+
+//create_associated_token_account instruction for transfers to wallets without token account
+//funded by payer which defaults to the fee payer of the transaction
+pub fn create_destination_instruction(
+    metadata: &SplTokenOperationMetadata,
+    fee_payer: Option<&Pubkey>,
+) -> Result<Option<Instruction>, ApiError> {
+    if !metadata.create_destination.unwrap_or(false) || metadata.destination_exists.unwrap_or(false)
+    {
+        return Ok(None);
+    }
+    let funder = match &metadata.payer {
+        Some(x) => Some(x.parse::<Pubkey>()?),
+        None => fee_payer.cloned(),
+    };
+    match (funder, &metadata.destination_owner, &metadata.mint) {
+        (Some(funder), Some(owner), Some(mint)) => Ok(Some(
+            spl_associated_token_account::create_associated_token_account(
+                &funder,
+                &owner.parse::<Pubkey>()?,
+                &mint.parse::<Pubkey>()?,
+            ),
+        )),
+        _ => Err(ApiError::BadOperations(
+            "destination_owner and mint required to create destination".to_string(),
+        )),
+    }
 }
 
 pub fn to_instruction(
@@ -21,12 +79,27 @@ pub fn to_instruction(
     metadata: SplTokenOperationMetadata,
 ) -> Result<Vec<Instruction>, ApiError> {
     let p;
+    let n;
+    let new_authority = if let Some(a) = &metadata.new_authority {
+        n = to_pub(a);
+        Some(&n)
+    } else {
+        None
+    };
     let freeze_authority = if let Some(f) = &metadata.freeze_authority {
         p = to_pub(f);
         Some(&p)
     } else {
         None
     };
+    let signers = metadata
+        .signers
+        .clone()
+        .unwrap_or_default()
+        .iter()
+        .map(|x| to_pub(x))
+        .collect::<Vec<Pubkey>>();
+    let signers = signers.iter().collect::<Vec<&Pubkey>>();
     let source = if let Some(s) = &metadata.source {
         to_pub(s)
     } else {
@@ -84,14 +157,24 @@ pub fn to_instruction(
             &source,
             &to_pub(&metadata.destination.unwrap()),
             &to_pub(&metadata.authority.unwrap()),
-            &vec![],
+            &signers,
             metadata.amount.unwrap(),
         )?],
+        OperationType::SplToken__ApproveChecked => vec![spl_token::instruction::approve_checked(
+            &spl_token::id(),
+            &source,
+            &to_pub(&metadata.mint.unwrap()),
+            &to_pub(&metadata.destination.unwrap()),
+            &to_pub(&metadata.authority.unwrap()),
+            &signers,
+            metadata.amount.unwrap(),
+            metadata.decimals.unwrap(),
+        )?],
         OperationType::SplToken__Revoke => vec![spl_token::instruction::revoke(
             &spl_token::id(),
             &source,
             &to_pub(&metadata.authority.unwrap()),
-            &vec![],
+            &signers,
         )?],
 
         OperationType::SplToken__MintTo => vec![spl_token::instruction::mint_to(
@@ -99,39 +182,77 @@ pub fn to_instruction(
             &to_pub(&metadata.mint.unwrap()),
             &source,
             &to_pub(&metadata.authority.unwrap()),
-            &vec![],
+            &signers,
+            metadata.amount.unwrap(),
+        )?],
+        OperationType::SplToken__MintToChecked => vec![spl_token::instruction::mint_to_checked(
+            &spl_token::id(),
+            &to_pub(&metadata.mint.unwrap()),
+            &source,
+            &to_pub(&metadata.authority.unwrap()),
+            &signers,
             metadata.amount.unwrap(),
+            metadata.decimals.unwrap(),
         )?],
 
         OperationType::SplToken__Burn => vec![spl_token::instruction::burn(
             &spl_token::id(),
             &source,
             &to_pub(&metadata.mint.unwrap()),
+            &to_pub(&metadata.delegate.or(metadata.authority).unwrap()),
+            &signers,
+            metadata.amount.unwrap(),
+        )?],
+        OperationType::SplToken__BurnChecked => vec![spl_token::instruction::burn_checked(
+            &spl_token::id(),
             &source,
-            &vec![],
+            &to_pub(&metadata.mint.unwrap()),
+            &to_pub(&metadata.delegate.or(metadata.authority).unwrap()),
+            &signers,
             metadata.amount.unwrap(),
+            metadata.decimals.unwrap(),
         )?],
-        OperationType::SplToken__CloseAccount => vec![spl_token::instruction::close_account(
+        OperationType::SplToken__SetAuthority => vec![spl_token::instruction::set_authority(
             &spl_token::id(),
             &source,
-            &to_pub(&metadata.authority.clone().unwrap()),
+            new_authority,
+            to_authority_type(metadata.authority_type.as_deref().unwrap_or_default())?,
             &to_pub(&metadata.authority.unwrap()),
-            &vec![],
+            &signers,
         )?],
+        OperationType::SplToken__CloseAccount => {
+            //rent goes to the owner unless another destination is given
+            let owner = to_pub(&metadata.authority.unwrap());
+            vec![spl_token::instruction::close_account(
+                &spl_token::id(),
+                &source,
+                &metadata.destination.map_or(owner, |x| to_pub(&x)),
+                &owner,
+                &signers,
+            )?]
+        }
         OperationType::SplToken__FreezeAccount => vec![spl_token::instruction::freeze_account(
             &spl_token::id(),
             &source,
             &to_pub(&metadata.mint.clone().unwrap()),
             &to_pub(&metadata.authority.unwrap()),
-            &vec![],
+            &signers,
         )?],
         OperationType::SplToken__ThawAccount => vec![spl_token::instruction::thaw_account(
             &spl_token::id(),
             &source,
             &to_pub(&metadata.mint.clone().unwrap()),
             &to_pub(&metadata.authority.unwrap()),
-            &vec![],
+            &signers,
         )?],
+        OperationType::SplToken__InitializeMultisig => {
+            vec![spl_token::instruction::initialize_multisig(
+                &spl_token::id(),
+                &source,
+                &signers,
+                metadata.required_signers.unwrap_or(signers.len() as u8),
+            )?]
+        }
         OperationType::SplToken__CreateAssocAccount => vec![
             spl_associated_token_account::create_associated_token_account(
                 &source,
@@ -145,8 +266,8 @@ pub fn to_instruction(
             &source,
             &to_pub(&metadata.mint.unwrap()),
             &to_pub(&metadata.destination.unwrap()),
-            &to_pub(&metadata.authority.unwrap()),
-            &vec![],
+            &to_pub(&metadata.delegate.or(metadata.authority).unwrap()),
+            &signers,
             metadata.amount.unwrap(),
             metadata.decimals.unwrap(),
         )?],
@@ -154,8 +275,8 @@ pub fn to_instruction(
             &spl_token::id(),
             &source,
             &to_pub(&metadata.destination.unwrap()),
-            &to_pub(&metadata.authority.unwrap()),
-            &vec![],
+            &to_pub(&metadata.delegate.or(metadata.authority).unwrap()),
+            &signers,
             metadata.amount.unwrap(),
         )?],
         _ => {
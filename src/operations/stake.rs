@@ -1,13 +1,17 @@
 use merge::Merge;
 use serde::{Deserialize, Serialize};
-use solana_sdk::instruction::Instruction;
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey};
 use solana_stake_program::{
     stake_instruction,
     stake_instruction::LockupArgs,
     stake_state::{Lockup, StakeAuthorize},
 };
 
-use crate::{error::ApiError, types::OperationType, utils::to_pub};
+use crate::{
+    error::ApiError,
+    types::OperationType,
+    utils::{to_pub, to_pub_optional},
+};
 #[derive(Merge, Default, Clone, Debug, Deserialize, Serialize)]
 pub struct StakeOperationMetadata {
     pub source: Option<String>,
@@ -19,6 +23,29 @@ pub struct StakeOperationMetadata {
     pub withdrawer: Option<String>,
     pub vote_pubkey: Option<String>,
     pub lockup: Option<LockupMeta>,
+    ///lockup custodian signing withdrawals of locked stake
+    pub custodian: Option<String>,
+    ///base and seed of seed derived stake account or authority
+    pub base: Option<String>,
+    pub seed: Option<String>,
+    ///program owning seed derived authority
+    pub owner: Option<String>,
+}
+impl StakeOperationMetadata {
+    //base defaults to source
+    fn base_and_seed(&self) -> Result<(Pubkey, String), ApiError> {
+        let base = to_pub(
+            self.base
+                .as_ref()
+                .or(self.source.as_ref())
+                .ok_or_else(|| ApiError::BadOperations("base missing".to_string()))?,
+        );
+        let seed = self
+            .seed
+            .clone()
+            .ok_or_else(|| ApiError::BadOperations("seed missing".to_string()))?;
+        Ok((base, seed))
+    }
 }
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct StakeOperation {
@@ -44,11 +71,8 @@ impl From<LockupMeta> for Lockup {
         Lockup {
             unix_timestamp: meta.unix_timestamp.unwrap_or(0),
             epoch: meta.epoch.unwrap_or(0),
-            custodian: to_pub(
-                &meta
-                    .custodian
-                    .unwrap_or("11111111111111111111111111111111".to_string()), //TODO: Find this
-            ),
+            //no custodian
+            custodian: meta.custodian.map_or(Pubkey::default(), |x| to_pub(&x)),
         }
     }
 }
@@ -89,7 +113,39 @@ pub fn to_instruction(
                 &to_pub(&metadata.destination.unwrap()),
                 &authorized,
                 &Lockup::from(metadata.lockup.unwrap()),
-                metadata.lamports.unwrap(),
+                metadata
+                    .lamports
+                    .ok_or_else(|| ApiError::BadOperations("lamports missing".to_string()))?,
+            )
+        }
+        OperationType::Stake__CreateAccountWithSeed => {
+            let (base, seed) = metadata.base_and_seed()?;
+            let address = Pubkey::create_with_seed(&base, &seed, &solana_stake_program::id())
+                .map_err(|e| ApiError::BadOperations(e.to_string()))?;
+            //stake account given in the operation has to be the derived one
+            if let Some(x) = &metadata.destination {
+                if *x != address.to_string() {
+                    return Err(ApiError::BadOperations(format!(
+                        "{} is not derived from base {} and seed {}",
+                        x, base, seed
+                    )));
+                }
+            }
+            let source = metadata.source.clone().unwrap();
+            let authorized = solana_stake_program::stake_state::Authorized {
+                staker: to_pub(metadata.staker.as_ref().unwrap_or(&source)),
+                withdrawer: to_pub(metadata.withdrawer.as_ref().unwrap_or(&source)),
+            };
+            stake_instruction::create_account_with_seed(
+                &to_pub(&source),
+                &address,
+                &base,
+                &seed,
+                &authorized,
+                &Lockup::from(metadata.lockup.unwrap_or_default()),
+                metadata
+                    .lamports
+                    .ok_or_else(|| ApiError::BadOperations("lamports missing".to_string()))?,
             )
         }
         OperationType::Stake__Delegate => vec![stake_instruction::delegate_stake(
@@ -128,13 +184,44 @@ pub fn to_instruction(
             }
             inx
         }
+        OperationType::Stake__AuthorizeWithSeed => {
+            let (base, seed) = metadata.base_and_seed()?;
+            let owner = to_pub(
+                metadata
+                    .owner
+                    .as_ref()
+                    .ok_or_else(|| ApiError::BadOperations("owner missing".to_string()))?,
+            );
+            let stake_pubkey = to_pub(&metadata.destination.unwrap());
+            let mut inx = vec![];
+            if let Some(x) = &metadata.staker {
+                inx.push(stake_instruction::authorize_with_seed(
+                    &stake_pubkey,
+                    &base,
+                    seed.clone(),
+                    &owner,
+                    &to_pub(x),
+                    StakeAuthorize::Staker,
+                ))
+            }
+            if let Some(x) = &metadata.withdrawer {
+                inx.push(stake_instruction::authorize_with_seed(
+                    &stake_pubkey,
+                    &base,
+                    seed,
+                    &owner,
+                    &to_pub(x),
+                    StakeAuthorize::Withdrawer,
+                ))
+            }
+            inx
+        }
         OperationType::Stake__Withdraw => vec![stake_instruction::withdraw(
             &to_pub(&metadata.source.unwrap()),
             &to_pub(&metadata.withdrawer.unwrap()),
             &to_pub(&metadata.destination.unwrap()),
             metadata.lamports.unwrap(),
-            //to_pub_optional(metadata.custodian),
-            None,
+            to_pub_optional(metadata.custodian).as_ref(),
         )],
         OperationType::Stake__Deactivate => vec![stake_instruction::deactivate_stake(
             &to_pub(&metadata.destination.unwrap()),
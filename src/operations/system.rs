@@ -1,7 +1,7 @@
 use crate::{error::ApiError, types::OperationType, utils::to_pub};
 use merge::Merge;
 use serde::{Deserialize, Serialize};
-use solana_sdk::{instruction::Instruction, system_instruction};
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, system_instruction, system_program};
 
 #[derive(Merge, Clone, Debug, Deserialize, Serialize, Default)]
 pub struct SystemOperationMetadata {
@@ -11,22 +11,57 @@ pub struct SystemOperationMetadata {
     pub lamports: Option<u64>,
     pub authority: Option<String>,
     pub new_authority: Option<String>,
+    ///program owning created or assigned account, system program by default
+    pub owner: Option<String>,
+    ///base and seed of seed derived account
+    pub base: Option<String>,
+    pub seed: Option<String>,
+}
+impl SystemOperationMetadata {
+    //address derived from base, seed and owner, must match the seeded account given in the operation
+    fn seeded_address(
+        &self,
+        base: Option<&String>,
+        seeded: Option<&String>,
+        owner: &Pubkey,
+    ) -> Result<(Pubkey, Pubkey, String), ApiError> {
+        let base = to_pub(base.ok_or_else(|| ApiError::BadOperations("base missing".to_string()))?);
+        let seed = self
+            .seed
+            .clone()
+            .ok_or_else(|| ApiError::BadOperations("seed missing".to_string()))?;
+        let address = Pubkey::create_with_seed(&base, &seed, owner)
+            .map_err(|e| ApiError::BadOperations(e.to_string()))?;
+        if let Some(x) = seeded {
+            if *x != address.to_string() {
+                return Err(ApiError::BadOperations(format!(
+                    "{} is not derived from base {} and seed {}",
+                    x, base, seed
+                )));
+            }
+        }
+        Ok((address, base, seed))
+    }
 }
 pub fn to_instruction(
     type_: OperationType,
     metadata: SystemOperationMetadata,
 ) -> Result<Vec<Instruction>, ApiError> {
+    let owner = metadata
+        .owner
+        .as_ref()
+        .map_or(system_program::id(), |x| to_pub(x));
     let instructions = match type_ {
         OperationType::System__CreateAccount => vec![system_instruction::create_account(
             &to_pub(&metadata.source.unwrap()),
             &to_pub(&metadata.destination.unwrap()),
             metadata.lamports.unwrap(),
             metadata.space.unwrap(),
-            &spl_token::id(),
+            &owner,
         )],
         OperationType::System__Assign => vec![system_instruction::assign(
             &to_pub(&metadata.source.unwrap()),
-            &spl_token::id(),
+            &owner,
         )],
         OperationType::System__Transfer => vec![system_instruction::transfer(
             &to_pub(&metadata.source.unwrap()),
@@ -37,7 +72,10 @@ pub fn to_instruction(
             &to_pub(&metadata.source.unwrap()),
             &to_pub(&metadata.destination.unwrap()),
             &to_pub(&metadata.authority.unwrap()),
-            metadata.lamports.unwrap_or(1000000000),
+            //filled with rent exemption by construction_metadata
+            metadata
+                .lamports
+                .ok_or_else(|| ApiError::BadOperations("lamports missing".to_string()))?,
         ),
 
         OperationType::System__AdvanceNonce => vec![system_instruction::advance_nonce_account(
@@ -61,6 +99,62 @@ pub fn to_instruction(
             &to_pub(&metadata.source.unwrap()),
             metadata.space.unwrap(),
         )],
+        OperationType::System__CreateAccountWithSeed => {
+            //funding source is the base unless another base is given
+            let (address, base, seed) = metadata.seeded_address(
+                metadata.base.as_ref().or(metadata.source.as_ref()),
+                metadata.destination.as_ref(),
+                &owner,
+            )?;
+            vec![system_instruction::create_account_with_seed(
+                &to_pub(&metadata.source.unwrap()),
+                &address,
+                &base,
+                &seed,
+                metadata.lamports.unwrap(),
+                metadata.space.unwrap(),
+                &owner,
+            )]
+        }
+        OperationType::System__TransferWithSeed => {
+            let (address, base, seed) = metadata.seeded_address(
+                metadata.base.as_ref(),
+                metadata.source.as_ref(),
+                &owner,
+            )?;
+            vec![system_instruction::transfer_with_seed(
+                &address,
+                &base,
+                seed,
+                &owner,
+                &to_pub(&metadata.destination.unwrap()),
+                metadata.lamports.unwrap(),
+            )]
+        }
+        OperationType::System__AllocateWithSeed => {
+            let (address, base, seed) = metadata.seeded_address(
+                metadata.base.as_ref(),
+                metadata.source.as_ref(),
+                &owner,
+            )?;
+            vec![system_instruction::allocate_with_seed(
+                &address,
+                &base,
+                &seed,
+                metadata.space.unwrap(),
+                &owner,
+            )]
+        }
+        OperationType::System__AssignWithSeed => {
+            let (address, base, seed) = metadata.seeded_address(
+                metadata.base.as_ref(),
+                metadata.source.as_ref(),
+                &owner,
+            )?;
+            vec![system_instruction::assign_with_seed(
+                &address, &base, &seed, &owner,
+            )]
+        }
         _ => {
             return Err(ApiError::BadOperations("Invalid Operation".to_string()));
         }
@@ -25,8 +25,16 @@ pub struct OpMetaTokenAmount {
 #[derive(Clone, Debug, Serialize, Deserialize, Default)]
 struct OpMeta {
     ///owner of sender address
-    #[serde(skip_serializing_if = "Option::is_none", alias = "custodian")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        alias = "custodian",
+        alias = "multisigAuthority",
+        alias = "multisigOwner"
+    )]
     authority: Option<String>,
+    ///signers of multisig authority
+    #[serde(skip_serializing_if = "Option::is_none")]
+    signers: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     new_authority: Option<String>,
     ///sender token wallet address
@@ -62,8 +70,12 @@ struct OpMeta {
     #[serde(skip_serializing_if = "Option::is_none")]
     space: Option<u64>,
 
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "Option::is_none", alias = "sourceOwner")]
     owner: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", alias = "sourceBase")]
+    base: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", alias = "sourceSeed")]
+    seed: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none", alias = "voter")]
     staker: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -152,6 +164,22 @@ pub fn get_operations_from_encoded_tx(
                 };
                 if let UiInstruction::Parsed(ui_parsed_instruction) = &instruction {
                     match &ui_parsed_instruction {
+                        UiParsedInstruction::Parsed(parsed_instruction)
+                            if parsed_instruction.program == "spl-memo" =>
+                        {
+                            //memo is parsed as plain string
+                            operations.push(Operation {
+                                operation_identifier: oi,
+                                related_operations: None,
+                                type_: OperationType::SplMemo__Memo,
+                                status: status.clone(),
+                                account: None,
+                                amount: None,
+                                metadata: Some(serde_json::json!({
+                                    "memo": parsed_instruction.parsed,
+                                })),
+                            });
+                        }
                         UiParsedInstruction::Parsed(parsed_instruction) => {
                             let parsed_instruction_enum: ParsedInstructionEnum =
                                 serde_json::from_value(parsed_instruction.parsed.clone())
@@ -167,18 +195,26 @@ pub fn get_operations_from_encoded_tx(
                             if optype.is_balance_changing() {
                                 let parsed_meta = OpMeta::from(&Some(metadata.clone()));
                                 let mut parsed_meta_cloned = parsed_meta.clone();
+                                //checked instructions have decimals in tokenAmount
+                                let token_decimals = parsed_meta
+                                    .token_amount
+                                    .as_ref()
+                                    .and_then(|x| x.decimals)
+                                    .map(|x| x as u8);
                                 let currency = Currency {
                                     symbol: parsed_meta
                                         .mint
                                         .unwrap_or(consts::NATIVE_SYMBOL.to_string()),
                                     decimals: parsed_meta
                                         .decimals
+                                        .or(token_decimals)
                                         .unwrap_or(consts::NATIVE_DECIMALS),
                                     metadata: None,
                                 };
                                 let sender = Some(AccountIdentifier {
                                     address: parsed_meta.source.unwrap(),
                                     sub_account: None,
+                                    metadata: None,
                                 });
                                 let sender_amt = Some(Amount {
                                     value: format!("-{}", parsed_meta_cloned.amount_str()),
@@ -187,6 +223,7 @@ pub fn get_operations_from_encoded_tx(
                                 let receiver = Some(AccountIdentifier {
                                     address: parsed_meta.destination.unwrap(),
                                     sub_account: None,
+                                    metadata: None,
                                 });
                                 let receiver_amt = Some(Amount {
                                     value: parsed_meta_cloned.amount_str(),
@@ -244,6 +281,33 @@ pub fn get_operations_from_encoded_tx(
                                 });
                             }
                         }
+                        UiParsedInstruction::PartiallyDecoded(partially_decoded_instruction)
+                            if partially_decoded_instruction.program_id
+                                == spl_associated_token_account::id().to_string()
+                                && partially_decoded_instruction.accounts.len() >= 4 =>
+                        {
+                            //not parsed by solana-transaction-status, rent is paid by funder
+                            //in an inner system transfer that depends on the cluster rent
+                            let accounts = &partially_decoded_instruction.accounts;
+                            operations.push(Operation {
+                                operation_identifier: oi,
+                                related_operations: None,
+                                type_: OperationType::SplToken__CreateAssocAccount,
+                                status: status.clone(),
+                                account: Some(AccountIdentifier {
+                                    address: accounts[0].clone(),
+                                    sub_account: None,
+                                    metadata: None,
+                                }),
+                                amount: None,
+                                metadata: Some(serde_json::json!({
+                                    "source": accounts[0],
+                                    "destination": accounts[1],
+                                    "owner": accounts[2],
+                                    "mint": accounts[3],
+                                })),
+                            });
+                        }
                         UiParsedInstruction::PartiallyDecoded(partially_decoded_instruction) => {
                             operations.push(Operation {
                                 operation_identifier: oi,
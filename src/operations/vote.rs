@@ -15,6 +15,8 @@ pub struct VoteOperationMetadata {
     pub destination: Option<String>,
     pub lamports: Option<u64>,
     pub authority: Option<String>,
+    ///validator identity, signs vote account creation
+    pub node_pubkey: Option<String>,
     pub voter: Option<String>,
     pub withdrawer: Option<String>,
     pub vote_pubkey: Option<String>,
@@ -26,13 +28,22 @@ pub fn to_instruction(
 ) -> Result<Vec<Instruction>, ApiError> {
     let instructions = match type_ {
         OperationType::Vote__CreateAccount => {
-            let authority = to_pub(&metadata.authority.unwrap());
-            //TODO: Add in meta later
+            //voter and withdrawer default to node identity
+            let node_pubkey = to_pub(
+                metadata
+                    .node_pubkey
+                    .as_ref()
+                    .or(metadata.authority.as_ref())
+                    .ok_or_else(|| ApiError::BadOperations("node_pubkey missing".to_string()))?,
+            );
             let vote_init = VoteInit {
-                node_pubkey: authority.clone(),
-                authorized_voter: authority.clone(),
-                authorized_withdrawer: authority.clone(),
-                commission: 100,
+                node_pubkey,
+                authorized_voter: metadata.voter.as_ref().map_or(node_pubkey, |x| to_pub(x)),
+                authorized_withdrawer: metadata
+                    .withdrawer
+                    .as_ref()
+                    .map_or(node_pubkey, |x| to_pub(x)),
+                commission: metadata.comission.unwrap_or(100),
             };
             vote_instruction::create_account(
                 &to_pub(&metadata.source.unwrap()),
@@ -0,0 +1,264 @@
+use std::{collections::HashSet, str::FromStr};
+
+use crate::{
+    construction::{check_limits, get_signing_payloads, pack_transactions},
+    consts,
+    error::ApiError,
+    is_bad_network,
+    operations::{
+        encode_tx,
+        spltoken::{self, SplTokenOperationMetadata},
+        system::{self, SystemOperationMetadata},
+    },
+    types::{OperationType, Payout, PayoutsRequest, PayoutsResponse, UnsignedTransaction},
+    Options,
+};
+use rocket_contrib::json::Json;
+use solana_sdk::{hash::Hash, instruction::Instruction, pubkey::Pubkey};
+use spl_associated_token_account::get_associated_token_address;
+
+//build transactions paying out from source wallet to many recipients
+pub fn construction_payouts(
+    payouts_request: PayoutsRequest,
+    options: &Options,
+) -> Result<Json<PayoutsResponse>, ApiError> {
+    is_bad_network(options, &payouts_request.network_identifier)?;
+
+    if payouts_request.payouts.is_empty() {
+        return Err(ApiError::BadOperations("payouts missing".to_string()));
+    }
+    let metadata = payouts_request.metadata.unwrap_or_default();
+    let source = payouts_request.source.address.parse::<Pubkey>()?;
+    let create_destination = metadata.create_destination.unwrap_or(false);
+    let mut created = HashSet::new();
+    let groups = payouts_request
+        .payouts
+        .iter()
+        .map(|x| payout_instructions(&source, x, create_destination, &mut created, options))
+        .collect::<Result<Vec<Vec<Instruction>>, ApiError>>()?;
+
+    let blockhash = if let Some(x) = &metadata.blockhash {
+        Hash::from_str(x).map_err(|_| ApiError::BadTransactionPayload)?
+    } else {
+        options.rpc.get_recent_blockhash()?.0
+    };
+    let (transactions, payout_transactions) = pack_transactions(groups, blockhash);
+    let transactions = transactions
+        .iter()
+        .map(|tx| {
+            check_limits(tx)?;
+            Ok(UnsignedTransaction {
+                unsigned_transaction: encode_tx(tx, metadata.encoding.unwrap_or_default())?,
+                payloads: get_signing_payloads(tx),
+            })
+        })
+        .collect::<Result<Vec<UnsignedTransaction>, ApiError>>()?;
+    Ok(Json(PayoutsResponse {
+        transactions,
+        payout_transactions,
+    }))
+}
+
+fn payout_instructions(
+    source: &Pubkey,
+    payout: &Payout,
+    create_destination: bool,
+    created: &mut HashSet<Pubkey>,
+    options: &Options,
+) -> Result<Vec<Instruction>, ApiError> {
+    let amount = payout
+        .amount
+        .value
+        .parse::<u64>()
+        .map_err(|_| ApiError::BadOperations(format!("bad amount {}", payout.amount.value)))?;
+    let recipient = payout.account.address.parse::<Pubkey>()?;
+    let currency = &payout.amount.currency;
+    if currency.symbol == consts::NATIVE_SYMBOL {
+        return system::to_instruction(
+            OperationType::System__Transfer,
+            SystemOperationMetadata {
+                source: Some(source.to_string()),
+                destination: Some(recipient.to_string()),
+                lamports: Some(amount),
+                ..Default::default()
+            },
+        );
+    }
+    //token payouts go between associated token accounts of the wallets
+    let mint = currency.symbol.parse::<Pubkey>()?;
+    let destination = get_associated_token_address(&recipient, &mint);
+    //recipient paid more than once gets its token account created by the first payout
+    let destination_exists = if !create_destination {
+        None
+    } else if !created.insert(destination) {
+        Some(true)
+    } else {
+        Some(
+            options
+                .rpc
+                .get_account_with_commitment(&destination, options.rpc.commitment())?
+                .value
+                .is_some(),
+        )
+    };
+    let metadata = SplTokenOperationMetadata {
+        source: Some(get_associated_token_address(source, &mint).to_string()),
+        destination: Some(destination.to_string()),
+        authority: Some(source.to_string()),
+        mint: Some(mint.to_string()),
+        amount: Some(amount),
+        decimals: Some(currency.decimals),
+        create_destination: Some(create_destination),
+        destination_owner: Some(recipient.to_string()),
+        destination_exists,
+        ..Default::default()
+    };
+    let mut instructions = vec![];
+    instructions.extend(spltoken::create_destination_instruction(
+        &metadata,
+        Some(source),
+    )?);
+    instructions.extend(spltoken::to_instruction(
+        OperationType::SplToken__TransferChecked,
+        metadata,
+    )?);
+    Ok(instructions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        create_rpc_client,
+        operations::get_tx_from_str,
+        types::{AccountIdentifier, Amount, Currency, NetworkIdentifier, PayoutsRequestMetadata},
+    };
+
+    fn account(address: &Pubkey) -> AccountIdentifier {
+        AccountIdentifier {
+            address: address.to_string(),
+            sub_account: None,
+            metadata: None,
+        }
+    }
+    fn payout(recipient: &Pubkey, symbol: String) -> Payout {
+        Payout {
+            account: account(recipient),
+            amount: Amount {
+                value: "100".to_string(),
+                currency: Currency {
+                    symbol,
+                    decimals: 9,
+                    metadata: None,
+                },
+            },
+        }
+    }
+    fn test_options() -> Options {
+        Options {
+            rpc: create_rpc_client("https://devnet.solana.com".to_string()),
+            network: "devnet".to_string(),
+            nonce_accounts: vec![],
+            nonce_index: Default::default(),
+        }
+    }
+    fn payouts_request(
+        source: &Pubkey,
+        payouts: Vec<Payout>,
+        create_destination: bool,
+    ) -> Result<PayoutsResponse, ApiError> {
+        construction_payouts(
+            PayoutsRequest {
+                network_identifier: NetworkIdentifier {
+                    blockchain: consts::BLOCKCHAIN.to_string(),
+                    network: "devnet".to_string(),
+                    sub_network_identifier: None,
+                },
+                source: account(source),
+                payouts,
+                metadata: Some(PayoutsRequestMetadata {
+                    blockhash: Some(Hash::new_unique().to_string()),
+                    create_destination: Some(create_destination),
+                    ..Default::default()
+                }),
+            },
+            &test_options(),
+        )
+        .map(|x| x.into_inner())
+    }
+
+    #[test]
+    fn payouts_test() {
+        let mint = Pubkey::new_unique();
+        let payouts = (0..30)
+            .map(|i| {
+                let symbol = if i % 2 == 0 {
+                    consts::NATIVE_SYMBOL.to_string()
+                } else {
+                    mint.to_string()
+                };
+                payout(&Pubkey::new_unique(), symbol)
+            })
+            .collect::<Vec<Payout>>();
+        let response = payouts_request(&Pubkey::new_unique(), payouts, false).unwrap();
+        assert!(response.transactions.len() > 1);
+        assert_eq!(response.payout_transactions.len(), 30);
+        for (i, tx) in response.transactions.iter().enumerate() {
+            let instructions = get_tx_from_str(&tx.unsigned_transaction)
+                .unwrap()
+                .message
+                .instructions
+                .len();
+            let payouts = response
+                .payout_transactions
+                .iter()
+                .filter(|x| **x == i)
+                .count();
+            assert_eq!(instructions, payouts);
+            assert_eq!(tx.payloads.len(), 1);
+        }
+
+        assert!(matches!(
+            payouts_request(&Pubkey::new_unique(), vec![], false),
+            Err(ApiError::BadOperations(_))
+        ));
+
+        //destination created by an earlier payout of the batch is not created again
+        let recipient = Pubkey::new_unique();
+        let mut created = HashSet::new();
+        created.insert(get_associated_token_address(&recipient, &mint));
+        let instructions = payout_instructions(
+            &Pubkey::new_unique(),
+            &payout(&recipient, mint.to_string()),
+            true,
+            &mut created,
+            &test_options(),
+        )
+        .unwrap();
+        assert_eq!(instructions.len(), 1);
+        assert_eq!(instructions[0].program_id, spl_token::id());
+    }
+
+    #[test]
+    #[ignore]
+    fn payouts_duplicate_recipient_test() {
+        let (mint, recipient) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let payouts = vec![
+            payout(&recipient, mint.to_string()),
+            payout(&recipient, mint.to_string()),
+        ];
+        let response = payouts_request(&Pubkey::new_unique(), payouts, true).unwrap();
+        let tx = get_tx_from_str(&response.transactions[0].unsigned_transaction).unwrap();
+        let creates = tx
+            .message
+            .instructions
+            .iter()
+            .filter(|x| {
+                tx.message.account_keys[x.program_id_index as usize]
+                    == spl_associated_token_account::id()
+            })
+            .count();
+        assert_eq!(creates, 1);
+        assert_eq!(tx.message.instructions.len(), 3);
+    }
+}
@@ -0,0 +1,269 @@
+use crate::{
+    consts,
+    error::ApiError,
+    is_bad_network,
+    operations::stake::LockupMeta,
+    types::{Amount, Currency, StakeAccountInfo, StakeRequest, StakeResponse},
+    Options,
+};
+use rocket_contrib::json::Json;
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, MemcmpEncodedBytes, RpcFilterType},
+    rpc_response::StakeActivationState,
+};
+use solana_sdk::{
+    account::{from_account, Account},
+    clock::{Clock, Epoch},
+    pubkey::Pubkey,
+    stake_history::StakeHistory,
+    sysvar,
+};
+use solana_stake_program::stake_state::StakeState;
+
+//current epoch and stake history needed for activation of stake accounts
+pub fn get_stake_context(options: &Options) -> Result<(Epoch, StakeHistory), ApiError> {
+    let clock = from_account::<Clock>(&options.rpc.get_account(&sysvar::clock::id())?)
+        .ok_or_else(|| ApiError::deserialization_failed("Clock"))?;
+    let history =
+        from_account::<StakeHistory>(&options.rpc.get_account(&sysvar::stake_history::id())?)
+            .ok_or_else(|| ApiError::deserialization_failed("StakeHistory"))?;
+    Ok((clock.epoch, history))
+}
+
+//decode stake account and its activation at epoch, same as getStakeActivation rpc
+pub fn get_stake_account_info(
+    address: &Pubkey,
+    account: &Account,
+    epoch: Epoch,
+    history: &StakeHistory,
+) -> Result<StakeAccountInfo, ApiError> {
+    if account.owner != solana_stake_program::id() {
+        return Err(ApiError::BadRequest);
+    }
+    let state =
+        StakeState::from(account).ok_or_else(|| ApiError::deserialization_failed("StakeState"))?;
+    let meta = state.meta();
+    let rent_exempt_reserve = meta.map_or(0, |x| x.rent_exempt_reserve);
+    let mut info = StakeAccountInfo {
+        address: address.to_string(),
+        lamports: account.lamports,
+        state: StakeActivationState::Inactive,
+        staker: meta.map(|x| x.authorized.staker.to_string()),
+        withdrawer: meta.map(|x| x.authorized.withdrawer.to_string()),
+        lockup: meta.map(|x| LockupMeta {
+            unix_timestamp: Some(x.lockup.unix_timestamp),
+            epoch: Some(x.lockup.epoch),
+            custodian: Some(x.lockup.custodian.to_string()),
+        }),
+        vote_pubkey: None,
+        activation_epoch: None,
+        deactivation_epoch: None,
+        active: 0,
+        inactive: account.lamports.saturating_sub(rent_exempt_reserve),
+        activating: 0,
+        deactivating: 0,
+        rent_exempt_reserve: meta.map(|x| x.rent_exempt_reserve),
+    };
+    if let Some(stake) = state.stake() {
+        let delegation = stake.delegation;
+        let (active, activating, deactivating) =
+            delegation.stake_activating_and_deactivating(epoch, Some(history), true);
+        info.state = if deactivating > 0 {
+            StakeActivationState::Deactivating
+        } else if activating > 0 {
+            StakeActivationState::Activating
+        } else if active > 0 {
+            StakeActivationState::Active
+        } else {
+            StakeActivationState::Inactive
+        };
+        let inactive = match info.state {
+            StakeActivationState::Activating => activating,
+            StakeActivationState::Active => 0,
+            StakeActivationState::Deactivating => delegation.stake.saturating_sub(active),
+            StakeActivationState::Inactive => delegation.stake,
+        };
+        info.vote_pubkey = Some(delegation.voter_pubkey.to_string());
+        info.activation_epoch = Some(delegation.activation_epoch);
+        if delegation.deactivation_epoch != Epoch::MAX {
+            info.deactivation_epoch = Some(delegation.deactivation_epoch);
+        }
+        info.active = active;
+        info.activating = activating;
+        info.deactivating = deactivating;
+        //undelegated lamports above rent reserve are inactive too
+        info.inactive = inactive
+            .saturating_add(account.lamports)
+            .saturating_sub(delegation.stake)
+            .saturating_sub(rent_exempt_reserve);
+    }
+    Ok(info)
+}
+
+//offsets of authorities in serialized StakeState after enum tag and rent_exempt_reserve
+const STAKER_OFFSET: usize = 12;
+const WITHDRAWER_OFFSET: usize = 44;
+
+//stake accounts with authority as staker or withdrawer
+pub fn get_stake_accounts_by_authority(
+    authority: &Pubkey,
+    options: &Options,
+) -> Result<Vec<(Pubkey, Account)>, ApiError> {
+    let mut accounts = vec![];
+    for offset in &[STAKER_OFFSET, WITHDRAWER_OFFSET] {
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![RpcFilterType::Memcmp(Memcmp {
+                offset: *offset,
+                bytes: MemcmpEncodedBytes::Binary(authority.to_string()),
+                encoding: None,
+            })]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                commitment: Some(options.rpc.commitment()),
+                ..RpcAccountInfoConfig::default()
+            },
+        };
+        accounts.extend(
+            options
+                .rpc
+                .get_program_accounts_with_config(&solana_stake_program::id(), config)?,
+        );
+    }
+    //same authority can be staker and withdrawer
+    accounts.sort_by_key(|x| x.0);
+    accounts.dedup_by_key(|x| x.0);
+    Ok(accounts)
+}
+
+//total lamports of stake accounts with activation and per account breakdown in metadata
+pub fn get_stake_balance(
+    accounts: &[(Pubkey, Account)],
+    epoch: Epoch,
+    history: &StakeHistory,
+) -> Result<Amount, ApiError> {
+    let infos = accounts
+        .iter()
+        .map(|(address, account)| get_stake_account_info(address, account, epoch, history))
+        .collect::<Result<Vec<StakeAccountInfo>, ApiError>>()?;
+    let sum = |f: fn(&StakeAccountInfo) -> u64| infos.iter().map(f).sum::<u64>();
+    Ok(Amount {
+        value: sum(|x| x.lamports).to_string(),
+        currency: Currency {
+            symbol: consts::NATIVE_SYMBOL.to_string(),
+            decimals: consts::NATIVE_DECIMALS,
+            metadata: Some(serde_json::json!({
+                "epoch": epoch,
+                "active": sum(|x| x.active),
+                "inactive": sum(|x| x.inactive),
+                "activating": sum(|x| x.activating),
+                "deactivating": sum(|x| x.deactivating),
+                "accounts": infos,
+            })),
+        },
+    })
+}
+
+pub fn stake_accounts(
+    stake_request: StakeRequest,
+    options: &Options,
+) -> Result<Json<StakeResponse>, ApiError> {
+    is_bad_network(options, &stake_request.network_identifier)?;
+
+    let (epoch, history) = get_stake_context(options)?;
+    let accounts = stake_request
+        .accounts
+        .iter()
+        .map(|x| {
+            let address = x.parse::<Pubkey>()?;
+            let account = options
+                .rpc
+                .get_account_with_commitment(&address, options.rpc.commitment())?
+                .value
+                .ok_or(ApiError::AccountNotFound)?;
+            get_stake_account_info(&address, &account, epoch, &history)
+        })
+        .collect::<Result<Vec<StakeAccountInfo>, ApiError>>()?;
+    Ok(Json(StakeResponse { epoch, accounts }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_stake_program::stake_state::{Authorized, Delegation, Lockup, Meta, Stake};
+
+    #[test]
+    fn stake_account_info_test() {
+        let (staker, vote) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let meta = Meta {
+            rent_exempt_reserve: 100,
+            authorized: Authorized::auto(&staker),
+            lockup: Lockup::default(),
+        };
+        let stake = Stake {
+            delegation: Delegation {
+                voter_pubkey: vote,
+                stake: 1000,
+                activation_epoch: 5,
+                ..Delegation::default()
+            },
+            credits_observed: 0,
+        };
+        let account = Account::new_data(
+            1200,
+            &StakeState::Stake(meta, stake),
+            &solana_stake_program::id(),
+        )
+        .unwrap();
+        let address = Pubkey::new_unique();
+        let history = StakeHistory::default();
+
+        let info = get_stake_account_info(&address, &account, 5, &history).unwrap();
+        assert!(matches!(info.state, StakeActivationState::Activating));
+        assert_eq!(info.activating, 1000);
+        assert_eq!(info.inactive, 1100);
+        assert_eq!(info.staker.unwrap(), staker.to_string());
+        assert_eq!(info.vote_pubkey.unwrap(), vote.to_string());
+        assert_eq!(info.deactivation_epoch, None);
+
+        let info = get_stake_account_info(&address, &account, 6, &history).unwrap();
+        assert!(matches!(info.state, StakeActivationState::Active));
+        assert_eq!(info.active, 1000);
+        assert_eq!(info.inactive, 100);
+        assert_eq!(info.rent_exempt_reserve, Some(100));
+
+        let account = Account::new_data(
+            1200,
+            &StakeState::Initialized(meta),
+            &solana_stake_program::id(),
+        )
+        .unwrap();
+        let info = get_stake_account_info(&address, &account, 6, &history).unwrap();
+        assert!(matches!(info.state, StakeActivationState::Inactive));
+        assert_eq!(info.inactive, 1100);
+        assert_eq!(info.vote_pubkey, None);
+
+        let stake_account = Account::new_data(
+            1200,
+            &StakeState::Stake(meta, stake),
+            &solana_stake_program::id(),
+        )
+        .unwrap();
+        let balance = get_stake_balance(
+            &[
+                (Pubkey::new_unique(), account),
+                (Pubkey::new_unique(), stake_account),
+            ],
+            6,
+            &history,
+        )
+        .unwrap();
+        assert_eq!(balance.value, "2400");
+        let metadata = balance.currency.metadata.unwrap();
+        assert_eq!(metadata["active"], 1000);
+        assert_eq!(metadata["inactive"], 1200);
+        assert_eq!(metadata["accounts"].as_array().unwrap().len(), 2);
+        assert_eq!(metadata["accounts"][1]["state"], "active");
+    }
+}
@@ -1,10 +1,13 @@
 use serde::{Deserialize, Serialize};
-use solana_client::rpc_request::RpcRequest;
+use solana_client::{rpc_request::RpcRequest, rpc_response::StakeActivationState};
 use solana_sdk::{clock::UnixTimestamp, fee_calculator::FeeCalculator, hash::Hash};
 use solana_transaction_status::UiTransactionStatusMeta;
 use spl_feature_proposal::instruction::FeatureProposalInstruction;
 
-use crate::operations::matcher::{InternalOperation, InternalOperationMetadata};
+use crate::operations::{
+    matcher::{InternalOperation, InternalOperationMetadata},
+    stake::LockupMeta,
+};
 
 // Objects
 
@@ -241,15 +244,15 @@ pub enum OperationType {
     System__CreateAccount,
     System__Assign,
     System__Transfer,
-    //    System__CreateAccountWithSeed,
+    System__CreateAccountWithSeed,
     System__CreateNonceAccount,
     System__AdvanceNonce,
     System__WithdrawFromNonce,
     System__AuthorizeNonce,
     System__Allocate,
-    //System__AllocateWithSeed,
-    //System__AssignWithSeed,
-    //System__TransferWithSeed,
+    System__AllocateWithSeed,
+    System__AssignWithSeed,
+    System__TransferWithSeed,
     SplToken__InitializeMint,
     SplToken__InitializeAccount,
     SplToken__CreateToken,
@@ -257,7 +260,7 @@ pub enum OperationType {
     SplToken__Transfer,
     SplToken__Approve,
     SplToken__Revoke,
-    //SplToken__SetAuthority,
+    SplToken__SetAuthority,
     SplToken__MintTo,
     SplToken__Burn,
     SplToken__CloseAccount,
@@ -265,12 +268,20 @@ pub enum OperationType {
     SplToken__ThawAccount,
     SplToken__TransferChecked,
     SplToken__CreateAssocAccount,
+    SplToken__InitializeMultisig,
+    SplToken__ApproveChecked,
+    SplToken__MintToChecked,
+    SplToken__BurnChecked,
+
+    SplMemo__Memo,
 
     Stake__CreateAccount,
+    Stake__CreateAccountWithSeed,
     Stake__Delegate,
     Stake__Split,
     Stake__Merge,
     Stake__Authorize,
+    Stake__AuthorizeWithSeed,
     Stake__Withdraw,
     Stake__Deactivate,
     Stake__SetLockup,
@@ -290,9 +301,14 @@ pub enum OperationType {
 //see transaction-status parse_x for names
 //operations wheres theres no equal negative and positive are not eligible for balance changing. e.g mint or burn where theres nobody on sending or receving end
 impl OperationType {
+    pub fn is_spl_token(&self) -> bool {
+        strum::AsStaticRef::as_static(self).starts_with("SplToken__")
+    }
     pub fn is_balance_changing(&self) -> bool {
         match &self {
             OperationType::System__CreateAccount
+            | OperationType::System__CreateAccountWithSeed
+            | OperationType::System__TransferWithSeed
             | OperationType::System__WithdrawFromNonce
             | OperationType::System__Transfer
             | OperationType::SplToken__Transfer
@@ -351,6 +367,8 @@ pub struct AccountIdentifier {
     pub address: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub sub_account: Option<SubAccountIdentifier>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<serde_json::Value>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -450,12 +468,29 @@ pub struct ConstructionCombineRequest {
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct ConstructionCombineResponse {
     pub signed_transaction: String,
+    ///signers whose signatures are still missing from signed_transaction
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pending_signers: Option<Vec<AccountIdentifier>>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct ConstructionDeriveRequest {
     pub network_identifier: NetworkIdentifier,
     pub public_key: PublicKey,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<ConstructionDeriveRequestMetadata>,
+}
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct ConstructionDeriveRequestMetadata {
+    ///derive associated token account of this mint
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mint: Option<String>,
+    ///derive owner address with seed from public key
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<String>,
+    ///program owning seed derived address, system program by default
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -479,6 +514,8 @@ pub struct ConstructionMetadataRequest {
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct ConstructionMetadataResponse {
     pub metadata: ConstructionMetadata,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggested_fee: Option<Vec<Amount>>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -507,6 +544,14 @@ pub struct ConstructionPayloadsRequest {
 pub struct ConstructionPayloadsResponse {
     pub unsigned_transaction: String,
     pub payloads: Vec<Option<SigningPayload>>,
+    ///all transactions if operations are split, first one is also returned above
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transactions: Option<Vec<UnsignedTransaction>>,
+}
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct UnsignedTransaction {
+    pub unsigned_transaction: String,
+    pub payloads: Vec<Option<SigningPayload>>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -520,6 +565,11 @@ pub struct ConstructionPreprocessRequest {
 pub struct ConstructionPreprocessRequestMetadata {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub with_nonce: Option<WithNonce>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<TransactionEncoding>,
+    ///split operations into several transactions if they don't fit in one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub split: Option<bool>,
 }
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct ConstructionPreprocessResponse {
@@ -637,6 +687,11 @@ pub struct Version {
 pub struct MetadataOptions {
     pub internal_operations: Vec<InternalOperation>,
     pub with_nonce: Option<WithNonce>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<TransactionEncoding>,
+    ///split operations into several transactions if they don't fit in one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub split: Option<bool>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -648,16 +703,177 @@ pub struct ErrorDetails {
 pub type OptionalInternalOperationMetadatas = Option<Vec<Option<InternalOperationMetadata>>>;
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct ConstructionMetadata {
+    ///can be left out in offline mode if with_nonce.blockhash is given
+    #[serde(default)]
     pub blockhash: String,
+    #[serde(default)]
     pub fee_calculator: FeeCalculator,
+    #[serde(default)]
     pub internal_meta: OptionalInternalOperationMetadatas,
+    #[serde(default)]
     pub with_nonce: Option<WithNonce>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<TransactionEncoding>,
+    ///split operations into several transactions if they don't fit in one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub split: Option<bool>,
+}
+///Encoding of serialized transactions returned by payloads and combine
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum TransactionEncoding {
+    #[serde(rename = "base58")]
+    Base58,
+    #[serde(rename = "base64")]
+    Base64,
+    #[serde(rename = "hex")]
+    Hex,
+}
+impl Default for TransactionEncoding {
+    fn default() -> Self {
+        TransactionEncoding::Base58
+    }
 }
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct WithNonce {
-    pub account: String,
+    ///selected from NONCE_ACCOUNTS pool if missing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub authority: Option<String>,
+    ///blockhash stored in nonce account, required for offline construction
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blockhash: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PayoutsRequest {
+    pub network_identifier: NetworkIdentifier,
+    ///wallet paying the payouts and fees
+    pub source: AccountIdentifier,
+    pub payouts: Vec<Payout>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<PayoutsRequestMetadata>,
+}
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Payout {
+    ///recipient wallet, token payouts go to its associated token account
+    pub account: AccountIdentifier,
+    pub amount: Amount,
+}
+#[derive(Clone, Debug, Deserialize, Serialize, Default)]
+pub struct PayoutsRequestMetadata {
+    ///required in offline mode
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blockhash: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<TransactionEncoding>,
+    ///create missing associated token accounts of recipients (online only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub create_destination: Option<bool>,
+}
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PayoutsResponse {
+    pub transactions: Vec<UnsignedTransaction>,
+    ///index of transaction of every payout
+    pub payout_transactions: Vec<usize>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct NonceRequest {
+    pub network_identifier: NetworkIdentifier,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accounts: Option<Vec<String>>,
+}
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct NonceResponse {
+    pub accounts: Vec<NonceAccount>,
+}
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct NonceAccount {
+    pub address: String,
+    pub authority: String,
+    pub blockhash: String,
+    pub lamports_per_signature: u64,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct StakeRequest {
+    pub network_identifier: NetworkIdentifier,
+    pub accounts: Vec<String>,
+}
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct StakeResponse {
+    pub epoch: u64,
+    pub accounts: Vec<StakeAccountInfo>,
+}
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct StakeAccountInfo {
+    pub address: String,
+    pub lamports: u64,
+    pub state: StakeActivationState,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub staker: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub withdrawer: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lockup: Option<LockupMeta>,
+    ///vote account stake is delegated to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vote_pubkey: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub activation_epoch: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deactivation_epoch: Option<u64>,
+    pub active: u64,
+    pub inactive: u64,
+    pub activating: u64,
+    pub deactivating: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rent_exempt_reserve: Option<u64>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct VoteRequest {
+    pub network_identifier: NetworkIdentifier,
+    pub account: String,
+    ///epochs to get rewards of, last completed epoch by default
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub epochs: Option<Vec<u64>>,
+}
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct VoteResponse {
+    pub account: VoteAccountInfo,
+    pub rewards: Vec<VoteReward>,
+}
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct VoteAccountInfo {
+    pub address: String,
+    pub lamports: u64,
+    pub node_pubkey: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub authorized_voter: Option<String>,
+    pub authorized_withdrawer: String,
+    pub commission: u8,
+    pub epoch_credits: Vec<EpochCredits>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_vote: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub root_slot: Option<u64>,
+    pub last_timestamp: UnixTimestamp,
+}
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct EpochCredits {
+    pub epoch: u64,
+    pub credits: u64,
+    pub previous_credits: u64,
+}
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct VoteReward {
+    pub epoch: u64,
+    ///block the reward was paid in
+    pub slot: u64,
+    pub lamports: i64,
+    pub post_balance: u64,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
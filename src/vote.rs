@@ -0,0 +1,207 @@
+use crate::{
+    consts,
+    error::ApiError,
+    is_bad_network,
+    types::{EpochCredits, VoteAccountInfo, VoteRequest, VoteResponse, VoteReward},
+    Options,
+};
+use rocket_contrib::json::Json;
+use solana_sdk::{account::Account, clock::Epoch, pubkey::Pubkey};
+use solana_transaction_status::{RewardType, Rewards};
+use solana_vote_program::vote_state::VoteState;
+
+//decode vote account state, authorized voter is the one of epoch
+pub fn get_vote_account_info(
+    address: &Pubkey,
+    account: &Account,
+    epoch: Epoch,
+) -> Result<VoteAccountInfo, ApiError> {
+    if account.owner != solana_vote_program::id() {
+        return Err(ApiError::BadRequest);
+    }
+    let state =
+        VoteState::from(account).ok_or_else(|| ApiError::deserialization_failed("VoteState"))?;
+    Ok(VoteAccountInfo {
+        address: address.to_string(),
+        lamports: account.lamports,
+        node_pubkey: state.node_pubkey.to_string(),
+        authorized_voter: state.get_authorized_voter(epoch).map(|x| x.to_string()),
+        authorized_withdrawer: state.authorized_withdrawer.to_string(),
+        commission: state.commission,
+        epoch_credits: state
+            .epoch_credits()
+            .iter()
+            .map(|(epoch, credits, previous_credits)| EpochCredits {
+                epoch: *epoch,
+                credits: *credits,
+                previous_credits: *previous_credits,
+            })
+            .collect(),
+        last_vote: state.last_voted_slot(),
+        root_slot: state.root_slot,
+        last_timestamp: state.last_timestamp.timestamp,
+    })
+}
+
+//voting reward of vote account in block rewards
+fn find_vote_reward(vote: &Pubkey, rewards: &Rewards) -> Option<(i64, u64)> {
+    let vote = vote.to_string();
+    rewards
+        .iter()
+        .find(|x| x.pubkey == vote && x.reward_type == Some(RewardType::Voting))
+        .map(|x| (x.lamports, x.post_balance))
+}
+
+//rewards of an epoch are paid in the first block of the next epoch
+pub fn get_vote_rewards(
+    vote: &Pubkey,
+    epochs: &[Epoch],
+    options: &Options,
+) -> Result<Vec<VoteReward>, ApiError> {
+    if epochs.len() > consts::MAX_REWARD_EPOCHS {
+        return Err(ApiError::BadRequest);
+    }
+    let next_epochs = epochs
+        .iter()
+        .map(|x| x.checked_add(1).ok_or(ApiError::BadRequest))
+        .collect::<Result<Vec<Epoch>, ApiError>>()?;
+    let schedule = options.rpc.get_epoch_schedule()?;
+    let mut rewards = vec![];
+    for (epoch, next_epoch) in epochs.iter().zip(next_epochs) {
+        let first_slot = schedule.get_first_slot_in_epoch(next_epoch);
+        let slot = match options
+            .rpc
+            .get_confirmed_blocks_with_limit(first_slot, 1)?
+            .first()
+        {
+            Some(x) => *x,
+            None => continue,
+        };
+        let block = options.rpc.get_confirmed_block(slot)?;
+        if let Some((lamports, post_balance)) = find_vote_reward(vote, &block.rewards) {
+            rewards.push(VoteReward {
+                epoch: *epoch,
+                slot,
+                lamports,
+                post_balance,
+            });
+        }
+    }
+    Ok(rewards)
+}
+
+pub fn vote_account(
+    vote_request: VoteRequest,
+    options: &Options,
+) -> Result<Json<VoteResponse>, ApiError> {
+    is_bad_network(options, &vote_request.network_identifier)?;
+
+    let address = vote_request.account.parse::<Pubkey>()?;
+    let account = options
+        .rpc
+        .get_account_with_commitment(&address, options.rpc.commitment())?
+        .value
+        .ok_or(ApiError::AccountNotFound)?;
+    let epoch = options.rpc.get_epoch_info()?.epoch;
+    let info = get_vote_account_info(&address, &account, epoch)?;
+    //last completed epoch by default
+    let epochs = vote_request
+        .epochs
+        .unwrap_or_else(|| (epoch.saturating_sub(1)..epoch).collect());
+    let rewards = get_vote_rewards(&address, &epochs, options)?;
+    Ok(Json(VoteResponse {
+        account: info,
+        rewards,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::create_rpc_client;
+    use solana_sdk::{clock::Clock, keyed_account::KeyedAccount};
+    use solana_transaction_status::Reward;
+    use solana_vote_program::vote_state::{self, VoteAuthorize, VoteInit, VoteStateVersions};
+    use std::{cell::RefCell, collections::HashSet};
+
+    #[test]
+    fn vote_account_info_test() {
+        let (node, voter, withdrawer) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let mut state = VoteState::new(
+            &VoteInit {
+                node_pubkey: node,
+                authorized_voter: voter,
+                authorized_withdrawer: withdrawer,
+                commission: 10,
+            },
+            &Clock::default(),
+        );
+        state.process_slot_vote_unchecked(5);
+        state.increment_credits(0);
+        let mut account = Account::new(100, VoteState::size_of(), &solana_vote_program::id());
+        VoteState::to(&VoteStateVersions::Current(Box::new(state)), &mut account).unwrap();
+        let address = Pubkey::new_unique();
+        //new voter takes over in epoch 3
+        let new_voter = Pubkey::new_unique();
+        let account = RefCell::new(account);
+        vote_state::authorize(
+            &KeyedAccount::new(&address, false, &account),
+            &new_voter,
+            VoteAuthorize::Voter,
+            &[voter].iter().cloned().collect::<HashSet<Pubkey>>(),
+            &Clock {
+                leader_schedule_epoch: 2,
+                ..Clock::default()
+            },
+        )
+        .unwrap();
+        let account = account.into_inner();
+        let info = get_vote_account_info(&address, &account, 3).unwrap();
+        assert_eq!(info.authorized_voter, Some(new_voter.to_string()));
+        let info = get_vote_account_info(&address, &account, 1).unwrap();
+        assert_eq!(info.node_pubkey, node.to_string());
+        assert_eq!(info.authorized_voter, Some(voter.to_string()));
+        assert_eq!(info.authorized_withdrawer, withdrawer.to_string());
+        assert_eq!(info.commission, 10);
+        assert_eq!(info.last_vote, Some(5));
+        assert_eq!(info.epoch_credits[0].credits, 1);
+
+        let reward = |pubkey: &Pubkey, reward_type| Reward {
+            pubkey: pubkey.to_string(),
+            lamports: 42,
+            post_balance: 142,
+            reward_type: Some(reward_type),
+        };
+        let rewards = vec![
+            reward(&node, RewardType::Fee),
+            reward(&address, RewardType::Voting),
+        ];
+        assert_eq!(find_vote_reward(&address, &rewards), Some((42, 142)));
+        assert_eq!(find_vote_reward(&node, &rewards), None);
+    }
+
+    #[test]
+    fn vote_rewards_epochs_test() {
+        let options = Options {
+            rpc: create_rpc_client("https://devnet.solana.com".to_string()),
+            network: "devnet".to_string(),
+            nonce_accounts: vec![],
+            nonce_index: Default::default(),
+        };
+        let vote = Pubkey::new_unique();
+        //rejected before any rpc call
+        assert!(matches!(
+            get_vote_rewards(&vote, &[Epoch::MAX], &options),
+            Err(ApiError::BadRequest)
+        ));
+        let epochs = (0..=consts::MAX_REWARD_EPOCHS as u64).collect::<Vec<Epoch>>();
+        assert!(matches!(
+            get_vote_rewards(&vote, &epochs, &options),
+            Err(ApiError::BadRequest)
+        ));
+    }
+}